@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::expr::Expr;
+use crate::modifiers::Modifiers;
+use crate::path::Pointer;
+use crate::ruuls::{
+    all_status, any_status, check_expression_with, check_selection, CheckOptions, Condition,
+    Constraint, FactConstraint, Status, Threshold,
+};
+use crate::selector::{Quantifier, Selector};
+
+/// `Condition` prepared once to be checked many times
+///
+/// Fields, including those of field comparisons and expressions, are turned into
+/// pointers when compiling, once per distinct field, and nested `And`s and `Or`s are
+/// merged into their parent. Checking gives the same `Status` as
+/// `Condition::check_status_with_options` with the options it was compiled with, but
+/// looks fields up without parsing or allocating and doesn't build results. Field
+/// comparisons still build their constraint from the referenced fact, and expressions
+/// their value, on every check.
+///
+/// The full `ConditionResult` tree is still available from the `Condition` itself.
+#[derive(Debug)]
+pub struct CompiledCondition {
+    root: Node,
+    /// Pointers of the fields, nodes refer to them by index
+    pointers: Vec<Pointer>,
+    options: CheckOptions,
+}
+
+#[derive(Debug)]
+enum Node {
+    And(Vec<Node>),
+    Or(Vec<Node>),
+    Not(Box<Node>),
+    Counting(Threshold, Vec<Node>),
+    Field {
+        pointer: usize,
+        constraint: Constraint,
+        modifiers: Modifiers,
+    },
+    FieldComparison {
+        pointer: usize,
+        fact: usize,
+        constraint: FactConstraint,
+        modifiers: Modifiers,
+    },
+    Expression {
+        expr: Expr,
+        /// Pointer of each field the expression reads, in `Expr::visit_facts` order
+        facts: Vec<usize>,
+        constraint: Constraint,
+        modifiers: Modifiers,
+    },
+    Selection {
+        select: Selector,
+        quantifier: Quantifier,
+        constraint: Constraint,
+        modifiers: Modifiers,
+    },
+    AnyElement {
+        pointer: usize,
+        any: Box<Node>,
+    },
+    AllElements {
+        pointer: usize,
+        all: Box<Node>,
+    },
+    NoElement {
+        pointer: usize,
+        none: Box<Node>,
+    },
+}
+
+impl CompiledCondition {
+    pub fn new(condition: Condition, options: CheckOptions) -> Self {
+        let mut compiler = Compiler {
+            options: &options,
+            fields: HashMap::new(),
+            pointers: Vec::new(),
+        };
+        let root = compiler.compile(condition);
+
+        CompiledCondition {
+            root,
+            pointers: compiler.pointers,
+            options,
+        }
+    }
+
+    /// Top-level `Status` of the condition for these facts
+    pub fn check_status(&self, info: &Value) -> Status {
+        self.check(&self.root, info)
+    }
+
+    fn check(&self, node: &Node, info: &Value) -> Status {
+        let options = &self.options;
+
+        match *node {
+            Node::And(ref and) => all_status(and.iter().map(|c| self.check(c, info))),
+            Node::Or(ref or) => any_status(or.iter().map(|c| self.check(c, info))),
+            Node::Not(ref not) => !self.check(not, info),
+            Node::Counting(threshold, ref conditions) => {
+                threshold.check(conditions.iter().map(|c| self.check(c, info)), options)
+            }
+            Node::Field {
+                pointer,
+                ref constraint,
+                ref modifiers,
+            } => match self.pointers[pointer].get(info) {
+                Some(s) => constraint.check_value_with_options(s, modifiers, options),
+                None => constraint.check_missing(),
+            },
+            Node::FieldComparison {
+                pointer,
+                fact,
+                ref constraint,
+                ref modifiers,
+            } => match (
+                self.pointers[pointer].get(info),
+                self.pointers[fact].get(info),
+            ) {
+                (Some(s), Some(other)) => match constraint
+                    .resolve(other, modifiers.coercion.unwrap_or(options.coercion))
+                {
+                    Some(c) => c.check_value_with_options(s, modifiers, options),
+                    None => options.type_mismatch.status(),
+                },
+                _ => Status::Unknown,
+            },
+            Node::Expression {
+                ref expr,
+                ref facts,
+                ref constraint,
+                ref modifiers,
+            } => {
+                let fact = |i: usize, _: &str| self.pointers[facts[i]].get(info);
+                check_expression_with(expr, constraint, modifiers, &fact, options).0
+            }
+            Node::Selection {
+                ref select,
+                quantifier,
+                ref constraint,
+                ref modifiers,
+            } => check_selection(select, quantifier, constraint, modifiers, info, options),
+            Node::AnyElement { pointer, ref any } => match self.elements(pointer, info) {
+                Ok(elements) => any_status(elements.iter().map(|e| self.check(any, e))),
+                Err(status) => status,
+            },
+            Node::AllElements { pointer, ref all } => match self.elements(pointer, info) {
+                Ok(elements) => all_status(elements.iter().map(|e| self.check(all, e))),
+                Err(status) => status,
+            },
+            Node::NoElement { pointer, ref none } => match self.elements(pointer, info) {
+                Ok(elements) => !any_status(elements.iter().map(|e| self.check(none, e))),
+                Err(status) => status,
+            },
+        }
+    }
+
+    /// Elements of the array at `pointer`, or the node's status if there's none
+    fn elements<'a>(&self, pointer: usize, info: &'a Value) -> Result<&'a [Value], Status> {
+        match self.pointers[pointer].get(info) {
+            Some(Value::Array(elements)) => Ok(elements),
+            Some(_) => Err(self.options.type_mismatch.status()),
+            None => Err(Status::Unknown),
+        }
+    }
+}
+
+impl From<Condition> for CompiledCondition {
+    fn from(condition: Condition) -> Self {
+        CompiledCondition::new(condition, CheckOptions::default())
+    }
+}
+
+struct Compiler<'a> {
+    options: &'a CheckOptions,
+    /// Index of each field's pointer in `pointers`
+    fields: HashMap<String, usize>,
+    pointers: Vec<Pointer>,
+}
+
+impl Compiler<'_> {
+    fn compile(&mut self, condition: Condition) -> Node {
+        match condition {
+            // `And`s and `Or`s are associative, so nested ones can be merged
+            Condition::And { and } => {
+                let mut nodes = Vec::with_capacity(and.len());
+                for c in and {
+                    match self.compile(c) {
+                        Node::And(children) => nodes.extend(children),
+                        node => nodes.push(node),
+                    }
+                }
+                Node::And(nodes)
+            }
+            Condition::Or { or } => {
+                let mut nodes = Vec::with_capacity(or.len());
+                for c in or {
+                    match self.compile(c) {
+                        Node::Or(children) => nodes.extend(children),
+                        node => nodes.push(node),
+                    }
+                }
+                Node::Or(nodes)
+            }
+            Condition::Not { not } => Node::Not(Box::new(self.compile(*not))),
+            Condition::AtLeast {
+                should_minimum_meet,
+                conditions,
+            } => self.counting(Threshold::AtLeast(should_minimum_meet), conditions),
+            Condition::Exactly {
+                exactly,
+                conditions,
+            } => self.counting(Threshold::Exactly(exactly), conditions),
+            Condition::AtMost {
+                at_most,
+                conditions,
+            } => self.counting(Threshold::AtMost(at_most), conditions),
+            Condition::None { none } => self.counting(Threshold::AtMost(0), none),
+            Condition::Condition {
+                field,
+                constraint,
+                modifiers,
+            } => Node::Field {
+                pointer: self.intern(field),
+                constraint,
+                modifiers,
+            },
+            Condition::FieldComparison {
+                field,
+                constraint,
+                modifiers,
+            } => Node::FieldComparison {
+                pointer: self.intern(field),
+                fact: self.intern(constraint.fact().fact.clone()),
+                constraint,
+                modifiers,
+            },
+            Condition::Expression {
+                expr,
+                constraint,
+                modifiers,
+            } => {
+                let mut facts = Vec::new();
+                expr.visit_facts(&mut |field| facts.push(self.intern(field.to_owned())));
+
+                Node::Expression {
+                    expr,
+                    facts,
+                    constraint,
+                    modifiers,
+                }
+            }
+            Condition::Selection {
+                select,
+                quantifier,
+                constraint,
+                modifiers,
+            } => Node::Selection {
+                select,
+                quantifier,
+                constraint,
+                modifiers,
+            },
+            Condition::AnyElement { field, any } => Node::AnyElement {
+                pointer: self.intern(field),
+                any: Box::new(self.compile(*any)),
+            },
+            Condition::AllElements { field, all } => Node::AllElements {
+                pointer: self.intern(field),
+                all: Box::new(self.compile(*all)),
+            },
+            Condition::NoElement { field, none } => Node::NoElement {
+                pointer: self.intern(field),
+                none: Box::new(self.compile(*none)),
+            },
+        }
+    }
+
+    fn counting(&mut self, threshold: Threshold, conditions: Vec<Condition>) -> Node {
+        Node::Counting(
+            threshold,
+            conditions.into_iter().map(|c| self.compile(c)).collect(),
+        )
+    }
+
+    fn intern(&mut self, field: String) -> usize {
+        if let Some(&i) = self.fields.get(&field) {
+            return i;
+        }
+
+        self.pointers
+            .push(Pointer::new(self.options.path_dialect, &field));
+        self.fields.insert(field, self.pointers.len() - 1);
+        self.pointers.len() - 1
+    }
+}
@@ -0,0 +1,204 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// Reads a fact as a point in time.
+///
+/// Strings are parsed as RFC 3339, numbers as seconds since the unix epoch.
+pub fn parse_timestamp(v: &Value) -> Option<DateTime<Utc>> {
+    match *v {
+        Value::String(ref s) => DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|t| t.with_timezone(&Utc)),
+        Value::Number(ref n) => {
+            if let Some(secs) = n.as_i64() {
+                Utc.timestamp_opt(secs, 0).single()
+            } else {
+                let secs = n.as_f64()?;
+                let (whole, nanos) = (secs.floor(), ((secs - secs.floor()) * 1e9).round());
+                // Rounding can reach a full second, e.g. for `0.9999999999`
+                let (whole, nanos) = if nanos >= 1e9 {
+                    (whole + 1.0, 0.0)
+                } else {
+                    (whole, nanos)
+                };
+                Utc.timestamp_opt(whole as i64, nanos as u32).single()
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Point in time given in a rule, as an RFC 3339 string or unix epoch seconds
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Timestamp(pub DateTime<Utc>);
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(t: DateTime<Utc>) -> Self {
+        Timestamp(t)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_rfc3339())
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let v = Value::deserialize(deserializer)?;
+        parse_timestamp(&v).map(Timestamp).ok_or_else(|| {
+            de::Error::custom(format!(
+                "invalid timestamp `{}`, expected an RFC 3339 string or unix epoch seconds",
+                v
+            ))
+        })
+    }
+}
+
+/// Length of time given in a rule, either as seconds or as a string made of
+/// `<number><unit>` parts with units `w`, `d`, `h`, `m` and `s`, e.g. `"1d12h"`.
+/// Negative lengths fail to load
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Span(pub Duration);
+
+impl From<Duration> for Span {
+    fn from(d: Duration) -> Self {
+        Span(d)
+    }
+}
+
+impl FromStr for Span {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().starts_with('-') {
+            return Err(format!("duration `{}` is negative", s));
+        }
+
+        let invalid = || format!("invalid duration `{}`, expected e.g. `30d` or `1h30m`", s);
+        let mut total = Duration::zero();
+        let mut digits = String::new();
+
+        for c in s.trim().chars() {
+            if c.is_ascii_digit() {
+                digits.push(c);
+                continue;
+            }
+
+            let n = digits.parse::<i64>().map_err(|_| invalid())?;
+            digits.clear();
+            let part = match c {
+                'w' => Duration::try_weeks(n),
+                'd' => Duration::try_days(n),
+                'h' => Duration::try_hours(n),
+                'm' => Duration::try_minutes(n),
+                's' => Duration::try_seconds(n),
+                _ => return Err(invalid()),
+            };
+            total = part
+                .and_then(|part| total.checked_add(&part))
+                .ok_or_else(|| format!("duration `{}` is too long", s))?;
+        }
+
+        if !digits.is_empty() || s.trim().is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Span(total))
+    }
+}
+
+impl Serialize for Span {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{}s", self.0.num_seconds()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Span {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::Number(n) if n.as_f64().is_some_and(|f| f < 0.0) => {
+                Err(de::Error::custom(format!("duration `{}` is negative", n)))
+            }
+            Value::Number(n) => n
+                .as_i64()
+                .and_then(Duration::try_seconds)
+                .map(Span)
+                .ok_or_else(|| de::Error::custom(format!("invalid duration `{}`", n))),
+            Value::String(s) => s.parse().map_err(de::Error::custom),
+            v => Err(de::Error::custom(format!("invalid duration `{}`", v))),
+        }
+    }
+}
+
+/// Days of the week, evaluated in an explicit timezone
+///
+/// The offset carried by the fact itself is ignored: the timestamp is converted to
+/// `timezone` (UTC if not given) before its weekday is taken.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaysOfWeek {
+    #[serde(with = "weekdays")]
+    pub days: Vec<Weekday>,
+    #[serde(default = "utc")]
+    pub timezone: Tz,
+}
+
+impl DaysOfWeek {
+    pub fn contains(&self, t: &DateTime<Utc>) -> bool {
+        self.days
+            .contains(&t.with_timezone(&self.timezone).weekday())
+    }
+}
+
+/// Window of the day `[start, end)`, evaluated in an explicit timezone
+///
+/// If `end` is before `start` the window wraps past midnight, e.g. `22:00` to `06:00`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeOfDayRange {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    #[serde(default = "utc")]
+    pub timezone: Tz,
+}
+
+impl TimeOfDayRange {
+    pub fn contains(&self, t: &DateTime<Utc>) -> bool {
+        let time = t.with_timezone(&self.timezone).time();
+
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            self.start <= time || time < self.end
+        }
+    }
+}
+
+fn utc() -> Tz {
+    Tz::UTC
+}
+
+mod weekdays {
+    use chrono::Weekday;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(days: &[Weekday], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(days.iter().map(|d| d.to_string().to_lowercase()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Weekday>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|d| {
+                d.parse::<Weekday>()
+                    .map_err(|_| de::Error::custom(format!("invalid day of week `{}`", d)))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,53 @@
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// Reads a fact as an exact decimal.
+///
+/// Numbers are parsed from their string form, which is the shortest representation of
+/// the `f64` unless the `arbitrary_precision` feature is on.
+pub fn parse_decimal(v: &Value) -> Option<Decimal> {
+    match *v {
+        Value::String(ref s) => from_str(s.trim()),
+        #[cfg(feature = "arbitrary_precision")]
+        Value::Number(ref n) => from_str(n.as_str()),
+        #[cfg(not(feature = "arbitrary_precision"))]
+        Value::Number(ref n) => from_str(&n.to_string()),
+        _ => None,
+    }
+}
+
+fn from_str(s: &str) -> Option<Decimal> {
+    Decimal::from_str(s)
+        .or_else(|_| Decimal::from_scientific(s))
+        .ok()
+}
+
+/// Exact decimal given in a rule, as a JSON number or a numeric string
+///
+/// Serialized as a string so that no precision is lost.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct DecimalNumber(pub Decimal);
+
+impl From<Decimal> for DecimalNumber {
+    fn from(d: Decimal) -> Self {
+        DecimalNumber(d)
+    }
+}
+
+impl Serialize for DecimalNumber {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for DecimalNumber {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let v = Value::deserialize(deserializer)?;
+        parse_decimal(&v)
+            .map(DecimalNumber)
+            .ok_or_else(|| de::Error::custom(format!("invalid decimal `{}`", v)))
+    }
+}
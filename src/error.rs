@@ -1,7 +1,17 @@
+use crate::expr::ExprParseError;
+use crate::ip::CidrParseError;
+use crate::selector::SelectorParseError;
+use crate::similarity::InvalidSimilarity;
+
+use globset::Error as GlobError;
+use regex::Error as RegexError;
 use reqwest::{header::InvalidHeaderValue, Error as ReqwestError};
+use semver::Error as SemverError;
 use serde_json::error::Error as SerializeJsonError;
+use std::net::AddrParseError;
 use thiserror::Error as ThisError;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(ThisError, Debug)]
 pub enum Error {
     #[error("Reqwest Error: `{0:?}`")]
@@ -10,6 +20,24 @@ pub enum Error {
     ReqwestInvalidHeaderError(#[from] InvalidHeaderValue),
     #[error("Serialize Json Error: `{0:?}`")]
     SerializeJsonError(#[from] SerializeJsonError),
+    #[error("Regex Error: `{0:?}`")]
+    RegexError(#[from] RegexError),
+    #[error("Glob Error: `{0:?}`")]
+    GlobError(#[from] GlobError),
+    #[error("Expression Parse Error: `{0:?}`")]
+    ExprParseError(#[from] ExprParseError),
+    #[error("Semver Error: `{0:?}`")]
+    SemverError(#[from] SemverError),
+    #[error("Selector Parse Error: `{0:?}`")]
+    SelectorParseError(#[from] SelectorParseError),
+    #[error("Cidr Parse Error: `{0:?}`")]
+    CidrParseError(#[from] CidrParseError),
+    #[error("Addr Parse Error: `{0:?}`")]
+    AddrParseError(#[from] AddrParseError),
+    #[error("Invalid Similarity Error: `{0:?}`")]
+    InvalidSimilarityError(#[from] InvalidSimilarity),
+    #[error("Type Mismatch Error: `{0}`")]
+    TypeMismatchError(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
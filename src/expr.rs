@@ -0,0 +1,565 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::{Number, Value};
+
+use crate::modifiers::Coercion;
+use crate::path::PathDialect;
+
+/// Arithmetic expression over facts and literals, used as the subject of a condition
+///
+/// It can be given either as a string such as `"price * quantity"` or
+/// `"abs(/end - /start)"`, or as JSON like `{"multiply": [{"fact": "price"}, {"fact": "quantity"}]}`.
+///
+/// In strings, a field starting with `/` is a JSON pointer, anything else is made of
+/// letters, digits, `_`, `.`, `[` and `]` and read in the rule's `PathDialect`, so
+/// `order.total` is a single key by default and a nested one with `PathDialect::Dot`.
+/// Use the JSON form for other field names.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Expr {
+    Fact(String),
+    Add(Vec<Expr>),
+    Subtract(Box<Expr>, Box<Expr>),
+    Multiply(Vec<Expr>),
+    Divide(Box<Expr>, Box<Expr>),
+    Modulo(Box<Expr>, Box<Expr>),
+    Abs(Box<Expr>),
+    Min(Vec<Expr>),
+    Max(Vec<Expr>),
+    Round(Box<Expr>),
+    #[serde(untagged)]
+    Literal(Number),
+}
+
+/// JSON form of `Expr`, `Expr` itself also accepts a string to parse
+#[derive(Deserialize)]
+#[serde(remote = "Expr", rename_all = "snake_case")]
+enum ExprDef {
+    Fact(String),
+    Add(Vec<Expr>),
+    Subtract(Box<Expr>, Box<Expr>),
+    Multiply(Vec<Expr>),
+    Divide(Box<Expr>, Box<Expr>),
+    Modulo(Box<Expr>, Box<Expr>),
+    Abs(Box<Expr>),
+    Min(Vec<Expr>),
+    Max(Vec<Expr>),
+    Round(Box<Expr>),
+    #[serde(untagged)]
+    Literal(Number),
+}
+
+impl<'de> Deserialize<'de> for Expr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::String(s) => s.parse().map_err(de::Error::custom),
+            v => ExprDef::deserialize(v).map_err(de::Error::custom),
+        }
+    }
+}
+
+/// Returned for a division or modulo by zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DivisionByZero;
+
+/// Why a sub-expression has no value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Invalid {
+    NotANumber,
+    DivisionByZero,
+}
+
+/// Intermediate result, keeping integers exact for as long as possible
+///
+/// Integers are held as `i128` so that both `i64` and `u64` facts are exact.
+#[derive(Debug, Clone, Copy)]
+enum Num {
+    Int(i128),
+    Float(f64),
+}
+
+impl Num {
+    fn from_value(v: &Value) -> Option<Num> {
+        v.as_i64()
+            .map(i128::from)
+            .or_else(|| v.as_u64().map(i128::from))
+            .map(Num::Int)
+            .or_else(|| v.as_f64().map(Num::Float))
+    }
+
+    /// Reads a fact operand, converting numeric strings unless `coercion` is strict
+    fn from_fact(v: &Value, coercion: Coercion) -> Option<Num> {
+        Num::from_value(v).or_else(|| {
+            let converted = coercion.for_int(v).or_else(|| coercion.for_float(v))?;
+            Num::from_value(&converted)
+        })
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Num::Int(i) => i as f64,
+            Num::Float(f) => f,
+        }
+    }
+
+    fn into_value(self) -> Value {
+        match self {
+            Num::Int(i) => match (i64::try_from(i), u64::try_from(i)) {
+                (Ok(i), _) => Value::from(i),
+                (_, Ok(u)) => Value::from(u),
+                _ => Num::Float(i as f64).into_value(),
+            },
+            Num::Float(f) => Number::from_f64(f).map_or(Value::Null, Value::Number),
+        }
+    }
+
+    fn combine(
+        self,
+        other: Num,
+        int: impl Fn(i128, i128) -> Option<i128>,
+        float: impl Fn(f64, f64) -> f64,
+    ) -> Num {
+        match (self, other) {
+            (Num::Int(a), Num::Int(b)) => {
+                int(a, b).map_or_else(|| Num::Float(float(a as f64, b as f64)), Num::Int)
+            }
+            (a, b) => Num::Float(float(a.as_f64(), b.as_f64())),
+        }
+    }
+}
+
+impl Expr {
+    /// Evaluates the expression against the facts.
+    ///
+    /// Returns `None` if any referenced fact is missing. Division or modulo by zero,
+    /// and operands that aren't numbers, evaluate to `null`.
+    pub fn evaluate(&self, info: &Value) -> Option<Value> {
+        self.evaluate_with_dialect(info, PathDialect::Pointer)
+    }
+
+    /// Same as `evaluate`, reading the fields in the given `PathDialect`
+    pub fn evaluate_with_dialect(&self, info: &Value, dialect: PathDialect) -> Option<Value> {
+        self.try_evaluate(
+            &|_, field| info.pointer(&dialect.to_pointer(field)),
+            Coercion::Strict,
+        )
+        .map(|v| v.unwrap_or(Value::Null))
+    }
+
+    /// Same as `evaluate` with `fact` looking up each field and facts converted to numbers
+    /// following the `Coercion` policy, but tells a division or modulo by zero apart from
+    /// the `null` of an operand that isn't a number
+    ///
+    /// `fact` is also given the position of the field among those `visit_facts` goes
+    /// through, so that fields resolved beforehand can be looked up by index.
+    pub(crate) fn try_evaluate<'a>(
+        &self,
+        fact: &dyn Fn(usize, &str) -> Option<&'a Value>,
+        coercion: Coercion,
+    ) -> Option<Result<Value, DivisionByZero>> {
+        let mut missing = false;
+        let mut i = 0;
+        self.visit_facts(&mut |field| {
+            missing |= fact(i, field).is_none();
+            i += 1;
+        });
+
+        if missing {
+            return None;
+        }
+
+        Some(match self.evaluate_num(fact, coercion, &mut 0) {
+            Ok(n) => Ok(n.into_value()),
+            Err(Invalid::NotANumber) => Ok(Value::Null),
+            Err(Invalid::DivisionByZero) => Err(DivisionByZero),
+        })
+    }
+
+    /// Calls `f` with every field the expression reads
+    pub(crate) fn visit_facts(&self, f: &mut impl FnMut(&str)) {
+        match *self {
+            Expr::Fact(ref field) => f(field),
+            Expr::Literal(_) => {}
+            Expr::Add(ref es) | Expr::Multiply(ref es) | Expr::Min(ref es) | Expr::Max(ref es) => {
+                es.iter().for_each(|e| e.visit_facts(f))
+            }
+            Expr::Subtract(ref a, ref b)
+            | Expr::Divide(ref a, ref b)
+            | Expr::Modulo(ref a, ref b) => {
+                a.visit_facts(f);
+                b.visit_facts(f);
+            }
+            Expr::Abs(ref e) | Expr::Round(ref e) => e.visit_facts(f),
+        }
+    }
+
+    fn evaluate_num<'a>(
+        &self,
+        fact: &dyn Fn(usize, &str) -> Option<&'a Value>,
+        coercion: Coercion,
+        next: &mut usize,
+    ) -> Result<Num, Invalid> {
+        let all = |es: &[Expr], next: &mut usize| {
+            es.iter()
+                .map(|e| e.evaluate_num(fact, coercion, next))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        match *self {
+            Expr::Fact(ref field) => {
+                let i = *next;
+                *next += 1;
+                fact(i, field)
+                    .and_then(|v| Num::from_fact(v, coercion))
+                    .ok_or(Invalid::NotANumber)
+            }
+            Expr::Literal(ref n) => {
+                Num::from_value(&Value::Number(n.clone())).ok_or(Invalid::NotANumber)
+            }
+            Expr::Add(ref es) => Ok(all(es, next)?.into_iter().fold(Num::Int(0), |a, b| {
+                a.combine(b, i128::checked_add, |a, b| a + b)
+            })),
+            Expr::Multiply(ref es) => Ok(all(es, next)?.into_iter().fold(Num::Int(1), |a, b| {
+                a.combine(b, i128::checked_mul, |a, b| a * b)
+            })),
+            Expr::Subtract(ref a, ref b) => Ok(a.evaluate_num(fact, coercion, next)?.combine(
+                b.evaluate_num(fact, coercion, next)?,
+                i128::checked_sub,
+                |a, b| a - b,
+            )),
+            Expr::Divide(ref a, ref b) => {
+                let (a, b) = (
+                    a.evaluate_num(fact, coercion, next)?,
+                    b.evaluate_num(fact, coercion, next)?,
+                );
+                if b.as_f64() == 0.0 {
+                    return Err(Invalid::DivisionByZero);
+                }
+
+                Ok(a.combine(
+                    b,
+                    |a, b| match a.checked_rem(b) {
+                        Some(0) => a.checked_div(b),
+                        _ => None,
+                    },
+                    |a, b| a / b,
+                ))
+            }
+            Expr::Modulo(ref a, ref b) => {
+                let (a, b) = (
+                    a.evaluate_num(fact, coercion, next)?,
+                    b.evaluate_num(fact, coercion, next)?,
+                );
+                if b.as_f64() == 0.0 {
+                    return Err(Invalid::DivisionByZero);
+                }
+
+                Ok(a.combine(b, i128::checked_rem, |a, b| a % b))
+            }
+            Expr::Abs(ref e) => Ok(match e.evaluate_num(fact, coercion, next)? {
+                Num::Int(i) => i
+                    .checked_abs()
+                    .map_or(Num::Float((i as f64).abs()), Num::Int),
+                Num::Float(f) => Num::Float(f.abs()),
+            }),
+            Expr::Min(ref es) => all(es, next)?
+                .into_iter()
+                .reduce(|a, b| if b.as_f64() < a.as_f64() { b } else { a })
+                .ok_or(Invalid::NotANumber),
+            Expr::Max(ref es) => all(es, next)?
+                .into_iter()
+                .reduce(|a, b| if b.as_f64() > a.as_f64() { b } else { a })
+                .ok_or(Invalid::NotANumber),
+            Expr::Round(ref e) => Ok(match e.evaluate_num(fact, coercion, next)? {
+                Num::Int(i) => Num::Int(i),
+                Num::Float(f) => {
+                    let rounded = f.round();
+                    if rounded >= i128::MIN as f64 && rounded < i128::MAX as f64 {
+                        Num::Int(rounded as i128)
+                    } else {
+                        Num::Float(rounded)
+                    }
+                }
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, es: &[Expr], sep: &str| -> fmt::Result {
+            for (i, e) in es.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(sep)?;
+                }
+                write!(f, "{}", e)?;
+            }
+            Ok(())
+        };
+
+        match *self {
+            Expr::Fact(ref field) => f.write_str(field),
+            Expr::Literal(ref n) => write!(f, "{}", n),
+            Expr::Add(ref es) => {
+                f.write_str("(")?;
+                join(f, es, " + ")?;
+                f.write_str(")")
+            }
+            Expr::Multiply(ref es) => {
+                f.write_str("(")?;
+                join(f, es, " * ")?;
+                f.write_str(")")
+            }
+            Expr::Subtract(ref a, ref b) => write!(f, "({} - {})", a, b),
+            Expr::Divide(ref a, ref b) => write!(f, "({} / {})", a, b),
+            Expr::Modulo(ref a, ref b) => write!(f, "({} % {})", a, b),
+            Expr::Abs(ref e) => write!(f, "abs({})", e),
+            Expr::Round(ref e) => write!(f, "round({})", e),
+            Expr::Min(ref es) => {
+                f.write_str("min(")?;
+                join(f, es, ", ")?;
+                f.write_str(")")
+            }
+            Expr::Max(ref es) => {
+                f.write_str("max(")?;
+                join(f, es, ", ")?;
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+// ***********************************************************************
+// PARSER
+// **********************************************************************
+/// Error returned when an expression string can't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExprParseError {
+    pub message: String,
+    /// Byte offset in the expression where the error was found
+    pub position: usize,
+}
+
+impl fmt::Display for ExprParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ExprParseError {}
+
+impl FromStr for Expr {
+    type Err = ExprParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { src: s, pos: 0 };
+        let expr = parser.expr()?;
+        parser.skip_whitespace();
+
+        if parser.pos < s.len() {
+            return Err(parser.error("unexpected input"));
+        }
+
+        Ok(expr)
+    }
+}
+
+/// Recursive descent parser:
+///
+/// ```text
+/// expr    := term (("+" | "-") term)*
+/// term    := unary (("*" | "/" | "%") unary)*
+/// unary   := "-" unary | primary
+/// primary := number | field | func "(" expr ("," expr)* ")" | "(" expr ")"
+/// ```
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> ExprParseError {
+        ExprParseError {
+            message: message.into(),
+            position: self.pos,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while let Some(c) = self.peek().filter(|&c| f(c)) {
+            self.pos += c.len_utf8();
+        }
+        &self.src[start..self.pos]
+    }
+
+    fn expr(&mut self) -> Result<Expr, ExprParseError> {
+        let mut lhs = self.term()?;
+        loop {
+            if self.eat('+') {
+                let rhs = self.term()?;
+                lhs = match lhs {
+                    Expr::Add(mut es) => {
+                        es.push(rhs);
+                        Expr::Add(es)
+                    }
+                    lhs => Expr::Add(vec![lhs, rhs]),
+                };
+            } else if self.eat('-') {
+                lhs = Expr::Subtract(Box::new(lhs), Box::new(self.term()?));
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, ExprParseError> {
+        let mut lhs = self.unary()?;
+        loop {
+            if self.eat('*') {
+                let rhs = self.unary()?;
+                lhs = match lhs {
+                    Expr::Multiply(mut es) => {
+                        es.push(rhs);
+                        Expr::Multiply(es)
+                    }
+                    lhs => Expr::Multiply(vec![lhs, rhs]),
+                };
+            } else if self.eat('/') {
+                lhs = Expr::Divide(Box::new(lhs), Box::new(self.unary()?));
+            } else if self.eat('%') {
+                lhs = Expr::Modulo(Box::new(lhs), Box::new(self.unary()?));
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, ExprParseError> {
+        if self.eat('-') {
+            let e = self.unary()?;
+            let negated = match e {
+                Expr::Literal(ref n) => n
+                    .as_i64()
+                    .and_then(i64::checked_neg)
+                    .map(Number::from)
+                    .or_else(|| n.as_f64().and_then(|f| Number::from_f64(-f))),
+                _ => None,
+            };
+
+            Ok(match negated {
+                Some(n) => Expr::Literal(n),
+                None => Expr::Subtract(Box::new(Expr::Literal(0.into())), Box::new(e)),
+            })
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, ExprParseError> {
+        self.skip_whitespace();
+        let start = self.pos;
+
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let e = self.expr()?;
+                if !self.eat(')') {
+                    return Err(self.error("expected `)`"));
+                }
+                Ok(e)
+            }
+            Some('/') => {
+                let pointer = self.take_while(|c| c.is_alphanumeric() || "_~/".contains(c));
+                if pointer.len() == 1 {
+                    self.pos = start;
+                    return Err(self.error("expected a field after `/`"));
+                }
+                Ok(Expr::Fact(pointer.into()))
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let literal = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                let n = if let Ok(i) = literal.parse::<i64>() {
+                    Number::from(i)
+                } else if let Ok(u) = literal.parse::<u64>() {
+                    Number::from(u)
+                } else {
+                    literal
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(Number::from_f64)
+                        .ok_or_else(|| ExprParseError {
+                            message: format!("invalid number `{}`", literal),
+                            position: start,
+                        })?
+                };
+                Ok(Expr::Literal(n))
+            }
+            Some(c) if c.is_alphanumeric() || c == '_' => {
+                let name = self.take_while(|c| c.is_alphanumeric() || "_.[]".contains(c));
+                if self.eat('(') {
+                    self.call(name, start)
+                } else {
+                    Ok(Expr::Fact(name.into()))
+                }
+            }
+            Some(_) => Err(self.error("expected a number, field or `(`")),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+
+    fn call(&mut self, name: &str, start: usize) -> Result<Expr, ExprParseError> {
+        let mut args = vec![self.expr()?];
+        while self.eat(',') {
+            args.push(self.expr()?);
+        }
+        if !self.eat(')') {
+            return Err(self.error("expected `,` or `)`"));
+        }
+
+        let unary = |args: Vec<Expr>| -> Result<Box<Expr>, ExprParseError> {
+            let mut args = args.into_iter();
+            match (args.next(), args.next()) {
+                (Some(e), None) => Ok(Box::new(e)),
+                _ => Err(ExprParseError {
+                    message: format!("`{}` takes exactly one argument", name),
+                    position: start,
+                }),
+            }
+        };
+
+        match name {
+            "abs" => Ok(Expr::Abs(unary(args)?)),
+            "round" => Ok(Expr::Round(unary(args)?)),
+            "min" => Ok(Expr::Min(args)),
+            "max" => Ok(Expr::Max(args)),
+            _ => Err(ExprParseError {
+                message: format!("unknown function `{}`", name),
+                position: start,
+            }),
+        }
+    }
+}
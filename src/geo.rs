@@ -0,0 +1,192 @@
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+/// Mean radius of the earth, in kilometres
+const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// Location on the earth in degrees
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl Point {
+    fn is_valid(&self) -> bool {
+        (-90.0..=90.0).contains(&self.lat) && (-180.0..=180.0).contains(&self.lon)
+    }
+
+    /// Great-circle distance to `other` in kilometres, using the haversine formula
+    pub fn distance_km(&self, other: &Point) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.lon - self.lon).to_radians();
+
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
+
+/// Reads a fact given as `{"lat": .., "lon": ..}`
+pub fn parse_point(v: &Value) -> Option<Point> {
+    let point = Point {
+        lat: v.get("lat")?.as_f64()?,
+        lon: v.get("lon")?.as_f64()?,
+    };
+
+    if point.is_valid() {
+        Some(point)
+    } else {
+        None
+    }
+}
+
+/// Circle around a point, given in a rule as `{"lat": .., "lon": .., "radius_km": ..}`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct GeoRadius {
+    pub lat: f64,
+    pub lon: f64,
+    pub radius_km: f64,
+}
+
+impl GeoRadius {
+    pub fn contains(&self, point: &Point) -> bool {
+        let center = Point {
+            lat: self.lat,
+            lon: self.lon,
+        };
+
+        center.distance_km(point) <= self.radius_km
+    }
+}
+
+impl<'de> Deserialize<'de> for GeoRadius {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Raw {
+            lat: f64,
+            lon: f64,
+            radius_km: f64,
+        }
+
+        let raw = Raw::deserialize(deserializer)?;
+        let center = Point {
+            lat: raw.lat,
+            lon: raw.lon,
+        };
+
+        if !center.is_valid() {
+            return Err(de::Error::custom(format!(
+                "invalid coordinates `{}, {}`",
+                raw.lat, raw.lon
+            )));
+        }
+        if !raw.radius_km.is_finite() || raw.radius_km < 0.0 {
+            return Err(de::Error::custom(format!(
+                "invalid radius `{}`",
+                raw.radius_km
+            )));
+        }
+
+        Ok(GeoRadius {
+            lat: raw.lat,
+            lon: raw.lon,
+            radius_km: raw.radius_km,
+        })
+    }
+}
+
+/// GeoJSON `Polygon` or `MultiPolygon` geometry
+///
+/// Positions are `[lon, lat]` as in GeoJSON. The first ring of each polygon is
+/// its outer boundary, any others are holes. Edges are straight lines in
+/// longitude/latitude, which is precise enough for service areas that don't
+/// cross the antimeridian.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "coordinates")]
+pub enum GeoPolygon {
+    Polygon(Vec<Vec<[f64; 2]>>),
+    MultiPolygon(Vec<Vec<Vec<[f64; 2]>>>),
+}
+
+impl GeoPolygon {
+    pub fn contains(&self, point: &Point) -> bool {
+        match *self {
+            GeoPolygon::Polygon(ref rings) => polygon_contains(rings, point),
+            GeoPolygon::MultiPolygon(ref polygons) => {
+                polygons.iter().any(|rings| polygon_contains(rings, point))
+            }
+        }
+    }
+
+    fn polygons(&self) -> Vec<&Vec<Vec<[f64; 2]>>> {
+        match *self {
+            GeoPolygon::Polygon(ref rings) => vec![rings],
+            GeoPolygon::MultiPolygon(ref polygons) => polygons.iter().collect(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for GeoPolygon {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(tag = "type", content = "coordinates")]
+        enum Raw {
+            Polygon(Vec<Vec<[f64; 2]>>),
+            MultiPolygon(Vec<Vec<Vec<[f64; 2]>>>),
+        }
+
+        let polygon = match Raw::deserialize(deserializer)? {
+            Raw::Polygon(rings) => GeoPolygon::Polygon(rings),
+            Raw::MultiPolygon(polygons) => GeoPolygon::MultiPolygon(polygons),
+        };
+
+        for rings in polygon.polygons() {
+            if rings.is_empty() {
+                return Err(de::Error::custom("polygon has no rings"));
+            }
+
+            for ring in rings {
+                if ring.len() < 4 || ring.first() != ring.last() {
+                    return Err(de::Error::custom(
+                        "polygon rings must be closed and have at least 4 positions",
+                    ));
+                }
+                if let Some(&[lon, lat]) = ring
+                    .iter()
+                    .find(|&&[lon, lat]| !Point { lat, lon }.is_valid())
+                {
+                    return Err(de::Error::custom(format!(
+                        "invalid position `[{}, {}]`",
+                        lon, lat
+                    )));
+                }
+            }
+        }
+
+        Ok(polygon)
+    }
+}
+
+fn polygon_contains(rings: &[Vec<[f64; 2]>], point: &Point) -> bool {
+    let mut rings = rings.iter();
+
+    rings
+        .next()
+        .is_some_and(|outer| ring_contains(outer, point))
+        && rings.all(|hole| !ring_contains(hole, point))
+}
+
+/// Even-odd rule: a ray cast from the point crosses the ring an odd number of times
+fn ring_contains(ring: &[[f64; 2]], point: &Point) -> bool {
+    let (x, y) = (point.lon, point.lat);
+
+    ring.windows(2).fold(false, |inside, edge| {
+        let ([x1, y1], [x2, y2]) = (edge[0], edge[1]);
+        if (y1 > y) != (y2 > y) && x < (x2 - x1) * (y - y1) / (y2 - y1) + x1 {
+            !inside
+        } else {
+            inside
+        }
+    })
+}
@@ -0,0 +1,176 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::ruuls::Reason;
+
+/// Integer of any size, used by the int operator family
+///
+/// Values that fit in an `i128` (which covers both `i64` and `u64`) are kept
+/// inline so that comparing them doesn't allocate.
+#[derive(Debug, Clone)]
+pub enum Integer {
+    Small(i128),
+    Big(BigInt),
+}
+
+impl Integer {
+    /// Reads a fact as an integer.
+    ///
+    /// Numbers beyond the `i64`/`u64` range are read from their digits with the
+    /// `arbitrary_precision` feature. Without it they can only have been parsed as an
+    /// `f64` and so may not be exact, so this fails with `Reason::OutOfRange` for them,
+    /// and with no reason for anything else that isn't an integer, such as `5.0`.
+    pub fn from_value(v: &Value) -> Result<Integer, Option<Reason>> {
+        match *v {
+            Value::Number(ref n) => {
+                if let Some(i) = n.as_i64() {
+                    Ok(Integer::Small(i128::from(i)))
+                } else if let Some(u) = n.as_u64() {
+                    Ok(Integer::Small(i128::from(u)))
+                } else if let Some(i) = Integer::from_digits(n) {
+                    Ok(i)
+                } else if n.as_f64().is_some_and(|f| {
+                    // Below `i64::MIN` or above `u64::MAX`, so necessarily whole
+                    f < -(2f64.powi(63)) || f >= 2f64.powi(64)
+                }) {
+                    Err(Some(Reason::OutOfRange))
+                } else {
+                    Err(None)
+                }
+            }
+            _ => Err(None),
+        }
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    fn from_digits(n: &serde_json::Number) -> Option<Integer> {
+        n.as_str().parse().ok()
+    }
+
+    #[cfg(not(feature = "arbitrary_precision"))]
+    fn from_digits(_: &serde_json::Number) -> Option<Integer> {
+        None
+    }
+
+    fn to_big(&self) -> BigInt {
+        match *self {
+            Integer::Small(i) => BigInt::from(i),
+            Integer::Big(ref b) => b.clone(),
+        }
+    }
+}
+
+impl From<i32> for Integer {
+    fn from(i: i32) -> Self {
+        Integer::Small(i128::from(i))
+    }
+}
+
+impl From<i64> for Integer {
+    fn from(i: i64) -> Self {
+        Integer::Small(i128::from(i))
+    }
+}
+
+impl From<u64> for Integer {
+    fn from(u: u64) -> Self {
+        Integer::Small(i128::from(u))
+    }
+}
+
+impl From<i128> for Integer {
+    fn from(i: i128) -> Self {
+        Integer::Small(i)
+    }
+}
+
+impl From<BigInt> for Integer {
+    fn from(b: BigInt) -> Self {
+        i128::try_from(&b).map_or(Integer::Big(b), Integer::Small)
+    }
+}
+
+impl FromStr for Integer {
+    type Err = num_bigint::ParseBigIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<i128>()
+            .map(Integer::Small)
+            .or_else(|_| s.parse::<BigInt>().map(Integer::from))
+    }
+}
+
+impl fmt::Display for Integer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Integer::Small(i) => write!(f, "{}", i),
+            Integer::Big(ref b) => write!(f, "{}", b),
+        }
+    }
+}
+
+impl Ord for Integer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Integer::Small(a), Integer::Small(b)) => a.cmp(b),
+            (a, b) => a.to_big().cmp(&b.to_big()),
+        }
+    }
+}
+
+impl PartialOrd for Integer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Integer {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Integer {}
+
+impl Serialize for Integer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Integer::Small(i) => {
+                if let Ok(i) = i64::try_from(i) {
+                    serializer.serialize_i64(i)
+                } else if let Ok(u) = u64::try_from(i) {
+                    serializer.serialize_u64(u)
+                } else {
+                    serializer.serialize_str(&i.to_string())
+                }
+            }
+            Integer::Big(ref b) => serializer.serialize_str(&b.to_string()),
+        }
+    }
+}
+
+/// Rule literals may be JSON integers, or strings of digits for values beyond `u64`
+impl<'de> Deserialize<'de> for Integer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let v = Value::deserialize(deserializer)?;
+        match v {
+            Value::String(ref s) => s
+                .trim()
+                .parse()
+                .map_err(|_| de::Error::custom(format!("invalid integer `{}`", s))),
+            ref v => Integer::from_value(v).map_err(|reason| match reason {
+                Some(Reason::OutOfRange) => de::Error::custom(format!(
+                    "integer `{}` is too large, give it as a string instead",
+                    v
+                )),
+                _ => de::Error::custom(format!("invalid integer `{}`", v)),
+            }),
+        }
+    }
+}
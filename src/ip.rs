@@ -0,0 +1,200 @@
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// Reads a fact as an IP address, IPv4-mapped IPv6 addresses are read as IPv4
+pub fn parse_ip(v: &Value) -> Option<IpAddr> {
+    v.as_str()?.trim().parse().ok().map(canonical)
+}
+
+/// Turns `::ffff:a.b.c.d` into `a.b.c.d` so that both forms compare equal
+pub fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        ip => ip,
+    }
+}
+
+/// Private address ranges: RFC 1918 for IPv4, unique local `fc00::/7` for IPv6
+pub fn is_private(ip: IpAddr) -> bool {
+    match canonical(ip) {
+        IpAddr::V4(v4) => v4.is_private(),
+        IpAddr::V6(v6) => v6.segments()[0] & 0xfe00 == 0xfc00,
+    }
+}
+
+/// Error returned when a CIDR block can't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CidrParseError {
+    pub cidr: String,
+}
+
+impl fmt::Display for CidrParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid CIDR block `{}`", self.cidr)
+    }
+}
+
+impl std::error::Error for CidrParseError {}
+
+/// CIDR block such as `10.0.0.0/8` or `2001:db8::/32`, a bare address is a block of one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// First and last address of the block, as integers
+    fn range(&self) -> (u128, u128) {
+        let (bits, addr) = match self.addr {
+            IpAddr::V4(v4) => (32, u128::from(u32::from(v4))),
+            IpAddr::V6(v6) => (128, u128::from(v6)),
+        };
+        let host_bits = bits - u32::from(self.prefix);
+        let mask = u128::MAX.checked_shl(host_bits).unwrap_or(0);
+        let host_mask = if host_bits == 0 {
+            0
+        } else {
+            u128::MAX >> (128 - host_bits)
+        };
+
+        (addr & mask, (addr & mask) | host_mask)
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = CidrParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || CidrParseError { cidr: s.into() };
+        let (addr, prefix) = match s.trim().split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s.trim(), None),
+        };
+
+        let addr = addr.parse::<IpAddr>().map_err(|_| error())?;
+        let bits = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse::<u8>().map_err(|_| error())?,
+            None => bits,
+        };
+
+        if prefix > bits {
+            return Err(error());
+        }
+
+        // An IPv4-mapped block only has an IPv4 form if its prefix covers the
+        // `::ffff:0:0/96` part, e.g. `::ffff:10.0.0.0/104` is `10.0.0.0/8`
+        match canonical(addr) {
+            IpAddr::V4(v4) if prefix >= 96 => Ok(Cidr {
+                addr: IpAddr::V4(v4),
+                prefix: prefix - 96,
+            }),
+            _ => Ok(Cidr { addr, prefix }),
+        }
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// List of CIDR blocks, given in a rule as a string or an array of strings
+///
+/// The blocks are merged into sorted, non-overlapping ranges when the rule is
+/// loaded, so looking up an address is a binary search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CidrSet {
+    cidrs: Vec<Cidr>,
+    v4: Vec<(u128, u128)>,
+    v6: Vec<(u128, u128)>,
+}
+
+impl CidrSet {
+    pub fn new(cidrs: &[&str]) -> Result<Self, CidrParseError> {
+        Ok(cidrs
+            .iter()
+            .map(|c| c.parse())
+            .collect::<Result<Vec<Cidr>, _>>()?
+            .into())
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let (ranges, n) = match canonical(ip) {
+            IpAddr::V4(v4) => (&self.v4, u128::from(u32::from(v4))),
+            IpAddr::V6(v6) => (&self.v6, u128::from(v6)),
+        };
+
+        // Index of the first range starting after `n`, the one before it may contain `n`
+        let i = ranges.partition_point(|&(start, _)| start <= n);
+        i > 0 && n <= ranges[i - 1].1
+    }
+}
+
+/// IPv4-mapped IPv6 addresses, `::ffff:0:0/96`
+const MAPPED: (u128, u128) = (0xffff_0000_0000, 0xffff_ffff_ffff);
+
+impl From<Vec<Cidr>> for CidrSet {
+    fn from(cidrs: Vec<Cidr>) -> Self {
+        let merge = |v4: bool| {
+            let mut ranges = cidrs
+                .iter()
+                .filter(|c| c.addr.is_ipv4() == v4)
+                .map(Cidr::range)
+                .collect::<Vec<_>>();
+            if v4 {
+                // Mapped addresses are looked up as IPv4, so IPv6 blocks wider than
+                // `::ffff:0:0/96` (e.g. `::/0`) contribute the IPv4 addresses they cover
+                ranges.extend(cidrs.iter().filter(|c| c.addr.is_ipv6()).filter_map(|c| {
+                    let (start, end) = c.range();
+                    let (start, end) = (start.max(MAPPED.0), end.min(MAPPED.1));
+                    (start <= end).then(|| (start - MAPPED.0, end - MAPPED.0))
+                }));
+            }
+            ranges.sort_unstable();
+
+            let mut merged: Vec<(u128, u128)> = Vec::with_capacity(ranges.len());
+            for (start, end) in ranges {
+                match merged.last_mut() {
+                    Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+                    _ => merged.push((start, end)),
+                }
+            }
+            merged
+        };
+
+        CidrSet {
+            v4: merge(true),
+            v6: merge(false),
+            cidrs,
+        }
+    }
+}
+
+impl Serialize for CidrSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.cidrs.iter().map(ToString::to_string))
+    }
+}
+
+impl<'de> Deserialize<'de> for CidrSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let cidrs = match Value::deserialize(deserializer)? {
+            Value::String(s) => vec![s],
+            v => Vec::<String>::deserialize(v).map_err(de::Error::custom)?,
+        };
+
+        cidrs
+            .iter()
+            .map(|c| c.parse())
+            .collect::<Result<Vec<Cidr>, _>>()
+            .map(CidrSet::from)
+            .map_err(de::Error::custom)
+    }
+}
@@ -57,10 +57,44 @@
 //!
 //! [1]: enum.Rule.html#method.check
 
+mod compiled;
+mod datetime;
+mod decimal;
 mod error;
+mod expr;
+mod geo;
+mod integer;
+mod ip;
+mod modifiers;
+mod path;
+mod pattern;
 mod ruuls;
-
-pub use crate::ruuls::{Condition, ConditionResult, Constraint, Status};
+mod selector;
+mod similarity;
+
+pub use crate::compiled::CompiledCondition;
+pub use crate::datetime::{DaysOfWeek, Span, TimeOfDayRange, Timestamp};
+pub use crate::decimal::DecimalNumber;
+pub use crate::error::{Error, Result};
+pub use crate::expr::{Expr, ExprParseError};
+pub use crate::geo::{GeoPolygon, GeoRadius, Point};
+pub use crate::integer::Integer;
+pub use crate::ip::{Cidr, CidrParseError, CidrSet};
+pub use crate::modifiers::{Coercion, Modifiers, Normalization};
+pub use crate::path::PathDialect;
+pub use crate::pattern::{GlobPattern, Pattern};
+pub use crate::ruuls::{
+    CheckOptions, Condition, ConditionResult, Constraint, Engine, Event, EventParams,
+    FactConstraint, FactReference, JsonType, Reason, Rule, RuleResult, Status, TypeMismatch,
+};
+pub use crate::selector::{Quantifier, Selector, SelectorParseError};
+pub use crate::similarity::{InvalidSimilarity, SimilarTo, Similarity};
+
+use chrono::{DateTime, Duration, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use rust_decimal::Decimal;
+use semver::{Version, VersionReq};
+use serde_json::Value;
 
 /// Creates a `Rule` where all child `Rule`s must be `Met`
 ///
@@ -80,9 +114,21 @@ pub fn or(or: Vec<Condition>) -> Condition {
     Condition::Or { or }
 }
 
+/// Creates a `Rule` that negates its child `Rule`
+///
+/// * If the child is `Met`, the result will be `NotMet`
+/// * If the child is `NotMet`, the result will be `Met`
+/// * If the child is `Unknown`, the result will be `Unknown`
+pub fn not(not: Condition) -> Condition {
+    Condition::Not { not: Box::new(not) }
+}
+
 /// Creates a `Rule` where `n` child `Rule`s must be `Met`
 ///
-/// * If `>= n` are `Met`, the result will be `Met`, otherwise it'll be `NotMet`
+/// * If `>= n` are `Met`, the result will be `Met`
+/// * If fewer than `n` could be `Met` even counting every `Unknown` child, the result will be `NotMet`
+/// * Otherwise the result will be `Unknown`, unless `CheckOptions::legacy_at_least` is set
+///   in which case it'll be `NotMet`
 pub fn at_least(should_minimum_meet: usize, conditions: Vec<Condition>) -> Condition {
     Condition::AtLeast {
         should_minimum_meet,
@@ -90,11 +136,80 @@ pub fn at_least(should_minimum_meet: usize, conditions: Vec<Condition>) -> Condi
     }
 }
 
+/// Creates a `Rule` where exactly `n` child `Rule`s must be `Met`
+///
+/// * If more than `n` are `Met`, or too few could possibly be `Met`, the result will be `NotMet`
+/// * If exactly `n` are `Met` and none are `Unknown`, the result will be `Met`
+/// * Otherwise the outcome depends on the `Unknown` children and the result will be `Unknown`
+pub fn exactly(exactly: usize, conditions: Vec<Condition>) -> Condition {
+    Condition::Exactly {
+        exactly,
+        conditions,
+    }
+}
+
+/// Creates a `Rule` where at most `n` child `Rule`s may be `Met`
+///
+/// * If more than `n` are `Met`, the result will be `NotMet`
+/// * If no more than `n` are `Met` even counting every `Unknown` child, the result will be `Met`
+/// * Otherwise the result will be `Unknown`
+pub fn at_most(at_most: usize, conditions: Vec<Condition>) -> Condition {
+    Condition::AtMost {
+        at_most,
+        conditions,
+    }
+}
+
+/// Creates a `Rule` where no child `Rule` may be `Met`
+///
+/// * If any are `Met`, the result will be `NotMet`
+/// * If the results contain only `NotMet` and `Unknown`, the result will be `Unknown`
+/// * Only results in `Met` if all children are `NotMet`
+pub fn none(none: Vec<Condition>) -> Condition {
+    Condition::None { none }
+}
+
+/// Creates a `Rule` where `condition` must be `Met` by at least one element of the array at `field`
+///
+/// Each element is the root of the facts given to `condition`, so a field `sku` refers to the element's `sku`.
+///
+/// * If the array is empty, the result will be `NotMet`
+/// * Otherwise the element results are combined like `or`
+pub fn any_element(field: &str, condition: Condition) -> Condition {
+    Condition::AnyElement {
+        field: field.into(),
+        any: Box::new(condition),
+    }
+}
+
+/// Creates a `Rule` where `condition` must be `Met` by every element of the array at `field`
+///
+/// * If the array is empty, the result will be `Met`
+/// * Otherwise the element results are combined like `and`
+pub fn all_elements(field: &str, condition: Condition) -> Condition {
+    Condition::AllElements {
+        field: field.into(),
+        all: Box::new(condition),
+    }
+}
+
+/// Creates a `Rule` where `condition` must not be `Met` by any element of the array at `field`
+///
+/// * If the array is empty, the result will be `Met`
+/// * Otherwise the result is the negation of `any_element`
+pub fn no_element(field: &str, condition: Condition) -> Condition {
+    Condition::NoElement {
+        field: field.into(),
+        none: Box::new(condition),
+    }
+}
+
 /// Creates a rule for string comparison
 pub fn string_equals(field: &str, val: &str) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::StringEquals(val.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -102,6 +217,7 @@ pub fn string_not_equals(field: &str, val: &str) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::StringNotEquals(val.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -109,6 +225,7 @@ pub fn string_contains(field: &str, val: &str) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::StringContains(val.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -116,6 +233,7 @@ pub fn string_does_not_contains(field: &str, val: &str) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::StringDoesNotContain(val.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -123,6 +241,7 @@ pub fn string_in(field: &str, val: Vec<&str>) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::StringIn(val.into_iter().map(ToOwned::to_owned).collect()),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -130,91 +249,225 @@ pub fn string_not_in(field: &str, val: Vec<&str>) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::StringNotIn(val.into_iter().map(ToOwned::to_owned).collect()),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule matching a string against a regular expression.
+///
+/// Fails if `pattern` is not a valid regex.
+pub fn string_matches(field: &str, pattern: &str) -> Result<Condition> {
+    Ok(Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::StringMatches(Pattern::new(pattern)?),
+        modifiers: Modifiers::default(),
+    })
+}
+
+pub fn string_starts_with(field: &str, val: &str) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::StringStartsWith(val.into()),
+        modifiers: Modifiers::default(),
+    }
+}
+
+pub fn string_ends_with(field: &str, val: &str) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::StringEndsWith(val.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
+pub fn string_includes_substring(field: &str, val: &str) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::StringIncludesSubstring(val.into()),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule matching a string against a shell-style glob such as `*.example.com`.
+///
+/// Fails if `pattern` is not a valid glob.
+pub fn glob(field: &str, pattern: &str) -> Result<Condition> {
+    Ok(Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::Glob(GlobPattern::new(pattern)?),
+        modifiers: Modifiers::default(),
+    })
+}
+
+/// Creates a rule for fuzzy string comparison, e.g. within a Levenshtein distance of
+/// `target`, failing if a `min_similarity` isn't between 0 and 1.
+pub fn string_similar(field: &str, target: &str, similarity: Similarity) -> Result<Condition> {
+    similarity.validate()?;
+
+    Ok(Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::StringSimilar(SimilarTo {
+            target: target.into(),
+            similarity,
+        }),
+        modifiers: Modifiers::default(),
+    })
+}
+
+/// Creates a rule for string comparison ignoring case.
+pub fn string_equals_ignore_case(field: &str, val: &str) -> Condition {
+    with_modifiers(
+        string_equals(field, val),
+        Modifiers {
+            case_insensitive: true,
+            ..Modifiers::default()
+        },
+    )
+}
+
+pub fn string_in_ignore_case(field: &str, val: Vec<&str>) -> Condition {
+    with_modifiers(
+        string_in(field, val),
+        Modifiers {
+            case_insensitive: true,
+            ..Modifiers::default()
+        },
+    )
+}
+
+/// Applies `modifiers` (case folding, unicode normalization, trimming, coercion)
+/// to every leaf rule in `condition`.
+pub fn with_modifiers(mut condition: Condition, modifiers: Modifiers) -> Condition {
+    condition.set_modifiers(&modifiers);
+    condition
+}
+
+/// Creates a rule applying `constraint` to every value picked by `selector`, such as
+/// `"/items/*/price"` or `"$.items[*].price"`, `Met` if any of them meets it.
+///
+/// Fails if `selector` can't be parsed. A `*` over an empty array or object selects
+/// nothing, which is `NotMet`. Only a path that doesn't exist in the facts is `Unknown`.
+pub fn select_any(selector: &str, constraint: Constraint) -> Result<Condition> {
+    Ok(Condition::Selection {
+        select: selector.parse()?,
+        quantifier: Quantifier::Any,
+        constraint,
+        modifiers: Modifiers::default(),
+    })
+}
+
+/// Same as `select_any`, `Met` if every selected value meets `constraint`, so a `*`
+/// over an empty array or object is `Met`.
+///
+/// A `*` branch where the rest of the selector is missing counts as a missing field,
+/// so an item without a price makes `"/items/*/price"` `Unknown` rather than `Met`.
+pub fn select_all(selector: &str, constraint: Constraint) -> Result<Condition> {
+    Ok(Condition::Selection {
+        select: selector.parse()?,
+        quantifier: Quantifier::All,
+        constraint,
+        modifiers: Modifiers::default(),
+    })
+}
+
 /// Creates a rule for int comparison.
-pub fn int_equals(field: &str, val: i64) -> Condition {
+pub fn int_equals(field: &str, val: impl Into<Integer>) -> Condition {
     Condition::Condition {
         field: field.into(),
-        constraint: Constraint::IntEquals(val),
+        constraint: Constraint::IntEquals(val.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
-pub fn int_not_equals(field: &str, val: i64) -> Condition {
+pub fn int_not_equals(field: &str, val: impl Into<Integer>) -> Condition {
     Condition::Condition {
         field: field.into(),
-        constraint: Constraint::IntNotEquals(val),
+        constraint: Constraint::IntNotEquals(val.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
-pub fn int_contains(field: &str, val: i64) -> Condition {
+pub fn int_contains(field: &str, val: impl Into<Integer>) -> Condition {
     Condition::Condition {
         field: field.into(),
-        constraint: Constraint::IntContains(val),
+        constraint: Constraint::IntContains(val.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
-pub fn int_does_not_contain(field: &str, val: i64) -> Condition {
+pub fn int_does_not_contain(field: &str, val: impl Into<Integer>) -> Condition {
     Condition::Condition {
         field: field.into(),
-        constraint: Constraint::IntDoesNotContain(val),
+        constraint: Constraint::IntDoesNotContain(val.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
-pub fn int_in(field: &str, val: Vec<i64>) -> Condition {
+pub fn int_in(field: &str, val: Vec<impl Into<Integer>>) -> Condition {
     Condition::Condition {
         field: field.into(),
-        constraint: Constraint::IntIn(val),
+        constraint: Constraint::IntIn(val.into_iter().map(Into::into).collect()),
+        modifiers: Modifiers::default(),
     }
 }
 
-pub fn int_not_in(field: &str, val: Vec<i64>) -> Condition {
+pub fn int_not_in(field: &str, val: Vec<impl Into<Integer>>) -> Condition {
     Condition::Condition {
         field: field.into(),
-        constraint: Constraint::IntNotIn(val),
+        constraint: Constraint::IntNotIn(val.into_iter().map(Into::into).collect()),
+        modifiers: Modifiers::default(),
     }
 }
 
-pub fn int_in_range(field: &str, start: i64, end: i64) -> Condition {
+pub fn int_in_range(field: &str, start: impl Into<Integer>, end: impl Into<Integer>) -> Condition {
     Condition::Condition {
         field: field.into(),
-        constraint: Constraint::IntInRange(start, end),
+        constraint: Constraint::IntInRange(start.into(), end.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
-pub fn int_not_in_range(field: &str, start: i64, end: i64) -> Condition {
+pub fn int_not_in_range(
+    field: &str,
+    start: impl Into<Integer>,
+    end: impl Into<Integer>,
+) -> Condition {
     Condition::Condition {
         field: field.into(),
-        constraint: Constraint::IntNotInRange(start, end),
+        constraint: Constraint::IntNotInRange(start.into(), end.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
-pub fn int_less_than(field: &str, val: i64) -> Condition {
+pub fn int_less_than(field: &str, val: impl Into<Integer>) -> Condition {
     Condition::Condition {
         field: field.into(),
-        constraint: Constraint::IntLessThan(val),
+        constraint: Constraint::IntLessThan(val.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
-pub fn int_less_than_inclusive(field: &str, val: i64) -> Condition {
+pub fn int_less_than_inclusive(field: &str, val: impl Into<Integer>) -> Condition {
     Condition::Condition {
         field: field.into(),
-        constraint: Constraint::IntLessThanInclusive(val),
+        constraint: Constraint::IntLessThanInclusive(val.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
-pub fn int_greater_than(field: &str, val: i64) -> Condition {
+pub fn int_greater_than(field: &str, val: impl Into<Integer>) -> Condition {
     Condition::Condition {
         field: field.into(),
-        constraint: Constraint::IntGreaterThan(val),
+        constraint: Constraint::IntGreaterThan(val.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
-pub fn int_greater_than_inclusive(field: &str, val: i64) -> Condition {
+pub fn int_greater_than_inclusive(field: &str, val: impl Into<Integer>) -> Condition {
     Condition::Condition {
         field: field.into(),
-        constraint: Constraint::IntGreaterThanInclusive(val),
+        constraint: Constraint::IntGreaterThanInclusive(val.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -223,6 +476,7 @@ pub fn float_equals(field: &str, val: f64) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::FloatEquals(val),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -230,6 +484,7 @@ pub fn float_not_equals(field: &str, val: f64) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::FloatNotEquals(val),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -237,6 +492,7 @@ pub fn float_contains(field: &str, val: f64) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::FloatContains(val),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -244,6 +500,7 @@ pub fn float_does_not_contain(field: &str, val: f64) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::FloatDoesNotContain(val),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -251,6 +508,7 @@ pub fn float_in(field: &str, val: Vec<f64>) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::FloatIn(val),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -258,6 +516,7 @@ pub fn float_not_in(field: &str, val: Vec<f64>) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::FloatNotIn(val),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -265,6 +524,7 @@ pub fn float_in_range(field: &str, start: f64, end: f64) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::FloatInRange(start, end),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -272,6 +532,7 @@ pub fn float_not_in_range(field: &str, start: f64, end: f64) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::FloatNotInRange(start, end),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -279,6 +540,7 @@ pub fn float_less_than(field: &str, val: f64) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::FloatLessThan(val),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -286,6 +548,7 @@ pub fn float_less_than_inclusive(field: &str, val: f64) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::FloatLessThanInclusive(val),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -293,6 +556,7 @@ pub fn float_greater_than(field: &str, val: f64) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::FloatGreaterThan(val),
+        modifiers: Modifiers::default(),
     }
 }
 
@@ -300,185 +564,2667 @@ pub fn float_greater_than_inclusive(field: &str, val: f64) -> Condition {
     Condition::Condition {
         field: field.into(),
         constraint: Constraint::FloatGreaterThanInclusive(val),
+        modifiers: Modifiers::default(),
     }
 }
 
-/// Creates a rule for boolean comparison.
-pub fn bool_equals(field: &str, val: bool) -> Condition {
+/// Creates a rule for exact decimal comparison, e.g. for currency amounts.
+///
+/// Numeric string facts such as `"19.99"` are compared exactly. JSON number facts are
+/// read by `serde_json` as `f64` unless the `arbitrary_precision` feature is on, so digits
+/// beyond `f64` precision are lost before the comparison: `0.30000000000000001` equals `0.3`.
+pub fn decimal_equals(field: &str, val: Decimal) -> Condition {
     Condition::Condition {
         field: field.into(),
-        constraint: Constraint::BoolEquals(val),
+        constraint: Constraint::DecimalEquals(val.into()),
+        modifiers: Modifiers::default(),
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::{and, at_least, bool_equals, int_equals, int_in_range, or, string_equals, Status};
-    use serde_json::{json, Value};
-
-    fn get_test_data() -> Value {
-        json!({
-            "foo": 1,
-            "bar": "bar",
-            "baz": true
-        })
+pub fn decimal_not_equals(field: &str, val: Decimal) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::DecimalNotEquals(val.into()),
+        modifiers: Modifiers::default(),
     }
+}
 
-    #[test]
-    fn and_rules() {
-        let map = get_test_data();
-        // Met & Met == Met
-        let mut root = and(vec![int_equals("foo", 1), string_equals("bar", "bar")]);
-        let mut res = root.check_value(&map);
-
-        assert!(res.status == Status::Met);
-
-        // Met & NotMet == NotMet
-        root = and(vec![int_equals("foo", 2), string_equals("bar", "bar")]);
-        res = root.check_value(&map);
-
-        assert!(res.status == Status::NotMet);
+pub fn decimal_in(field: &str, val: Vec<Decimal>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::DecimalIn(val.into_iter().map(Into::into).collect()),
+        modifiers: Modifiers::default(),
+    }
+}
 
-        // Met & Unknown == Unknown
-        root = and(vec![int_equals("quux", 2), string_equals("bar", "bar")]);
-        res = root.check_value(&map);
+pub fn decimal_not_in(field: &str, val: Vec<Decimal>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::DecimalNotIn(val.into_iter().map(Into::into).collect()),
+        modifiers: Modifiers::default(),
+    }
+}
 
-        assert!(res.status == Status::Unknown);
+pub fn decimal_in_range(field: &str, start: Decimal, end: Decimal) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::DecimalInRange(start.into(), end.into()),
+        modifiers: Modifiers::default(),
+    }
+}
 
-        // NotMet & Unknown == NotMet
-        root = and(vec![int_equals("quux", 2), string_equals("bar", "baz")]);
-        res = root.check_value(&map);
+pub fn decimal_not_in_range(field: &str, start: Decimal, end: Decimal) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::DecimalNotInRange(start.into(), end.into()),
+        modifiers: Modifiers::default(),
+    }
+}
 
-        assert!(res.status == Status::NotMet);
+pub fn decimal_less_than(field: &str, val: Decimal) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::DecimalLessThan(val.into()),
+        modifiers: Modifiers::default(),
+    }
+}
 
-        // Unknown & Unknown == Unknown
-        root = and(vec![int_equals("quux", 2), string_equals("fizz", "bar")]);
-        res = root.check_value(&map);
+pub fn decimal_less_than_inclusive(field: &str, val: Decimal) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::DecimalLessThanInclusive(val.into()),
+        modifiers: Modifiers::default(),
+    }
+}
 
-        assert!(res.status == Status::Unknown);
+pub fn decimal_greater_than(field: &str, val: Decimal) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::DecimalGreaterThan(val.into()),
+        modifiers: Modifiers::default(),
     }
+}
 
-    #[test]
-    fn or_rules() {
-        let map = get_test_data();
-        // Met | Met == Met
-        let mut root = or(vec![int_equals("foo", 1), string_equals("bar", "bar")]);
-        let mut res = root.check_value(&map);
+pub fn decimal_greater_than_inclusive(field: &str, val: Decimal) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::DecimalGreaterThanInclusive(val.into()),
+        modifiers: Modifiers::default(),
+    }
+}
 
-        assert!(res.status == Status::Met);
+/// Creates a rule for semantic version comparison, failing if `version` isn't valid.
+pub fn semver_equals(field: &str, version: &str) -> Result<Condition> {
+    Ok(Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::SemverEquals(Version::parse(version)?),
+        modifiers: Modifiers::default(),
+    })
+}
 
-        // Met | NotMet == Met
-        root = or(vec![int_equals("foo", 2), string_equals("bar", "bar")]);
-        res = root.check_value(&map);
+pub fn semver_greater_than(field: &str, version: &str) -> Result<Condition> {
+    Ok(Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::SemverGreaterThan(Version::parse(version)?),
+        modifiers: Modifiers::default(),
+    })
+}
 
-        assert!(res.status == Status::Met);
+pub fn semver_less_than(field: &str, version: &str) -> Result<Condition> {
+    Ok(Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::SemverLessThan(Version::parse(version)?),
+        modifiers: Modifiers::default(),
+    })
+}
 
-        // Met | Unknown == Met
-        root = or(vec![int_equals("quux", 2), string_equals("bar", "bar")]);
-        res = root.check_value(&map);
+pub fn semver_in_range(field: &str, start: &str, end: &str) -> Result<Condition> {
+    Ok(Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::SemverInRange(Version::parse(start)?, Version::parse(end)?),
+        modifiers: Modifiers::default(),
+    })
+}
 
-        assert!(res.status == Status::Met);
+/// Creates a rule checking a version against a requirement such as `">=2.1, <3"`.
+///
+/// Pre-releases only satisfy a requirement that names a pre-release of the same
+/// `major.minor.patch`.
+pub fn semver_satisfies(field: &str, req: &str) -> Result<Condition> {
+    Ok(Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::SemverSatisfies(VersionReq::parse(req)?),
+        modifiers: Modifiers::default(),
+    })
+}
 
-        // NotMet | Unknown == Unknown
-        root = or(vec![int_equals("quux", 2), string_equals("bar", "baz")]);
-        res = root.check_value(&map);
+/// Creates a rule checking an IP address against CIDR blocks such as `"10.0.0.0/8"`,
+/// failing if any block isn't valid.
+pub fn ip_in_cidr(field: &str, cidrs: Vec<&str>) -> Result<Condition> {
+    Ok(Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::IpInCidr(CidrSet::new(&cidrs)?),
+        modifiers: Modifiers::default(),
+    })
+}
 
-        assert!(res.status == Status::Unknown);
+pub fn ip_not_in_cidr(field: &str, cidrs: Vec<&str>) -> Result<Condition> {
+    Ok(Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::IpNotInCidr(CidrSet::new(&cidrs)?),
+        modifiers: Modifiers::default(),
+    })
+}
 
-        // Unknown | Unknown == Unknown
-        root = or(vec![int_equals("quux", 2), string_equals("fizz", "bar")]);
-        res = root.check_value(&map);
+pub fn ip_equals(field: &str, ip: &str) -> Result<Condition> {
+    Ok(Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::IpEquals(ip.parse()?),
+        modifiers: Modifiers::default(),
+    })
+}
 
-        assert!(res.status == Status::Unknown);
+/// Creates a rule checking that an IP address is in a private range.
+pub fn ip_is_private(field: &str) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::IpIsPrivate,
+        modifiers: Modifiers::default(),
     }
+}
 
-    #[test]
-    fn n_of_rules() {
-        let map = get_test_data();
-        // 2 Met, 1 NotMet == Met
-        let mut root = at_least(
-            2,
-            vec![
-                int_equals("foo", 1),
-                string_equals("bar", "bar"),
-                bool_equals("baz", false),
-            ],
-        );
-        let mut res = root.check_value(&map);
-
-        assert!(res.status == Status::Met);
+/// Creates a rule checking that a `{"lat": .., "lon": ..}` fact is within
+/// `radius_km` of the given point, failing if the point is out of range or the radius
+/// isn't a finite, non-negative distance.
+pub fn geo_within_radius(field: &str, lat: f64, lon: f64, radius_km: f64) -> Result<Condition> {
+    Ok(Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::GeoWithinRadius(serde_json::from_value(serde_json::json!({
+            "lat": lat,
+            "lon": lon,
+            "radius_km": radius_km,
+        }))?),
+        modifiers: Modifiers::default(),
+    })
+}
 
-        // 1 Met, 1 NotMet, 1 Unknown == NotMet
-        root = at_least(
-            2,
-            vec![
-                int_equals("foo", 1),
-                string_equals("quux", "bar"),
-                bool_equals("baz", false),
-            ],
-        );
-        res = root.check_value(&map);
+/// Creates a rule checking that a `{"lat": .., "lon": ..}` fact is inside a GeoJSON
+/// `Polygon` or `MultiPolygon`, failing if the geometry isn't valid.
+pub fn geo_in_polygon(field: &str, geojson: Value) -> Result<Condition> {
+    Ok(Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::GeoInPolygon(serde_json::from_value(geojson)?),
+        modifiers: Modifiers::default(),
+    })
+}
 
-        assert!(res.status == Status::NotMet);
+/// Creates a rule checking that an array fact contains every one of `vals`, which
+/// may be strings, ints or floats.
+pub fn contains_all(field: &str, vals: Vec<impl Into<Value>>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::ContainsAll(vals.into_iter().map(Into::into).collect()),
+        modifiers: Modifiers::default(),
+    }
+}
 
-        // 2 NotMet, 1 Unknown == Unknown
-        root = at_least(
-            2,
-            vec![
-                int_equals("foo", 2),
-                string_equals("quux", "baz"),
-                bool_equals("baz", false),
-            ],
-        );
-        res = root.check_value(&map);
+pub fn contains_any(field: &str, vals: Vec<impl Into<Value>>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::ContainsAny(vals.into_iter().map(Into::into).collect()),
+        modifiers: Modifiers::default(),
+    }
+}
 
-        assert!(res.status == Status::NotMet);
+pub fn contains_none(field: &str, vals: Vec<impl Into<Value>>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::ContainsNone(vals.into_iter().map(Into::into).collect()),
+        modifiers: Modifiers::default(),
     }
+}
 
-    #[test]
-    fn string_equals_rule() {
-        let map = get_test_data();
-        let mut rule = string_equals("bar", "bar");
-        let mut res = rule.check_value(&map);
-        assert!(res.status == Status::Met);
+/// Creates a rule checking that every element of an array fact is one of `vals`.
+pub fn subset_of(field: &str, vals: Vec<impl Into<Value>>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::SubsetOf(vals.into_iter().map(Into::into).collect()),
+        modifiers: Modifiers::default(),
+    }
+}
 
-        rule = string_equals("bar", "baz");
-        res = rule.check_value(&map);
-        assert!(res.status == Status::NotMet);
+pub fn superset_of(field: &str, vals: Vec<impl Into<Value>>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::SupersetOf(vals.into_iter().map(Into::into).collect()),
+        modifiers: Modifiers::default(),
+    }
+}
+
+pub fn intersects(field: &str, vals: Vec<impl Into<Value>>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::Intersects(vals.into_iter().map(Into::into).collect()),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule checking that an array fact has exactly `vals`, in the same order.
+pub fn array_equals(field: &str, vals: Vec<impl Into<Value>>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::ArrayEquals(vals.into_iter().map(Into::into).collect()),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule checking that an array fact has the same elements as `vals`, the
+/// same number of times, in any order.
+pub fn array_equals_unordered(field: &str, vals: Vec<impl Into<Value>>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::ArrayEqualsUnordered(vals.into_iter().map(Into::into).collect()),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule comparing a fact of any JSON type with `val`, structurally.
+///
+/// Numbers compare by value, so `1` equals `1.0`, also inside arrays and objects.
+pub fn equals(field: &str, val: impl Into<Value>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::Equals(val.into()),
+        modifiers: Modifiers::default(),
+    }
+}
+
+pub fn not_equals(field: &str, val: impl Into<Value>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::NotEquals(val.into()),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule checking that a fact of any JSON type is structurally equal to one
+/// of `vals` (the `in` operator).
+pub fn is_in(field: &str, vals: Vec<impl Into<Value>>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::In(vals.into_iter().map(Into::into).collect()),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule checking that a fact contains at least the keys and values of
+/// `val`, at any depth. Arrays in `val` match when each of their elements matches
+/// some element of the fact's array.
+pub fn matches_subset(field: &str, val: Value) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::MatchesSubset(val),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule for boolean comparison.
+pub fn bool_equals(field: &str, val: bool) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::BoolEquals(val),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule for date comparison.
+///
+/// Facts are read as RFC 3339 strings or unix epoch seconds.
+pub fn date_before(field: &str, val: DateTime<Utc>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::DateBefore(val.into()),
+        modifiers: Modifiers::default(),
+    }
+}
+
+pub fn date_after(field: &str, val: DateTime<Utc>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::DateAfter(val.into()),
+        modifiers: Modifiers::default(),
+    }
+}
+
+pub fn date_between(field: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::DateBetween(start.into(), end.into()),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule met when the date lies within `duration` before now.
+///
+/// "Now" is `CheckOptions::now` if set, the system clock otherwise.
+pub fn within_last(field: &str, duration: Duration) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::WithinLast(duration.into()),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule met when the date falls on one of `days` in `timezone`.
+pub fn day_of_week_in(field: &str, days: Vec<Weekday>, timezone: Tz) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::DayOfWeekIn(DaysOfWeek { days, timezone }),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule met when the time of day in `timezone` is in `[start, end)`.
+///
+/// If `end` is before `start` the window wraps past midnight.
+pub fn time_of_day_between(
+    field: &str,
+    start: NaiveTime,
+    end: NaiveTime,
+    timezone: Tz,
+) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::TimeOfDayBetween(TimeOfDayRange {
+            start,
+            end,
+            timezone,
+        }),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule met when the field is present, whatever its value.
+///
+/// Unlike other rules, a missing field is `NotMet` rather than `Unknown`.
+pub fn exists(field: &str) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::Exists,
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule met when the field is absent.
+pub fn not_exists(field: &str) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::NotExists,
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule met when the field is present and `null`.
+pub fn is_null(field: &str) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::IsNull,
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule met when the field is present and not `null`.
+pub fn is_not_null(field: &str) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::IsNotNull,
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule met when the field is present and of the given JSON type.
+pub fn is_type(field: &str, ty: JsonType) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::IsType(ty),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule for length comparison.
+///
+/// Strings are measured in chars, arrays in elements and objects in keys.
+pub fn length_equals(field: &str, len: usize) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::LengthEquals(len),
+        modifiers: Modifiers::default(),
+    }
+}
+
+pub fn length_greater_than(field: &str, len: usize) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::LengthGreaterThan(len),
+        modifiers: Modifiers::default(),
+    }
+}
+
+pub fn length_less_than(field: &str, len: usize) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::LengthLessThan(len),
+        modifiers: Modifiers::default(),
+    }
+}
+
+pub fn length_in_range(field: &str, start: usize, end: usize) -> Condition {
+    Condition::Condition {
+        field: field.into(),
+        constraint: Constraint::LengthInRange(start, end),
+        modifiers: Modifiers::default(),
+    }
+}
+
+/// Creates a rule applying `constraint` to the result of an arithmetic expression
+/// such as `"price * quantity"`, see [`Expr`] for the syntax.
+///
+/// * If a field used in the expression is missing, the result will be `Unknown`
+/// * Division or modulo by zero is `Unknown`, with `Reason::DivisionByZero`
+/// * Operands that aren't numbers evaluate to `null`
+pub fn expression(expr: &str, constraint: Constraint) -> Result<Condition> {
+    Ok(Condition::Expression {
+        expr: expr.parse()?,
+        constraint,
+        modifiers: Modifiers::default(),
+    })
+}
+
+/// Creates a rule comparing a field against another field rather than a literal.
+///
+/// * If either field is missing, the result will be `Unknown`
+pub fn field_comparison(field: &str, constraint: FactConstraint) -> Condition {
+    Condition::FieldComparison {
+        field: field.into(),
+        constraint,
+        modifiers: Modifiers::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        all_elements, and, any_element, array_equals, array_equals_unordered, at_least, at_most,
+        bool_equals, contains_all, contains_any, contains_none, date_after, date_before,
+        date_between, day_of_week_in, decimal_equals, decimal_greater_than,
+        decimal_greater_than_inclusive, decimal_in, decimal_in_range, decimal_not_equals, equals,
+        exactly, exists, expression, field_comparison, float_greater_than, geo_in_polygon,
+        geo_within_radius, glob, int_contains, int_equals, int_greater_than, int_in_range,
+        intersects, ip_equals, ip_in_cidr, ip_is_private, ip_not_in_cidr, is_in, is_not_null,
+        is_null, is_type, length_equals, length_greater_than, length_in_range, length_less_than,
+        matches_subset, no_element, none, not, not_equals, not_exists, or, select_all, select_any,
+        semver_equals, semver_greater_than, semver_in_range, semver_less_than, semver_satisfies,
+        string_ends_with, string_equals, string_equals_ignore_case, string_in,
+        string_in_ignore_case, string_includes_substring, string_matches, string_not_equals,
+        string_similar, string_starts_with, subset_of, superset_of, time_of_day_between,
+        with_modifiers, within_last, CheckOptions, Coercion, CompiledCondition, Condition,
+        Constraint, Engine, Error, FactConstraint, JsonType, Modifiers, Normalization, PathDialect,
+        Reason, Rule, Similarity, Status, TypeMismatch,
+    };
+    use chrono::{Duration, NaiveTime, TimeZone, Utc, Weekday};
+    use futures_util::FutureExt;
+    use rust_decimal::Decimal;
+    use serde_json::{json, Value};
+
+    fn get_test_data() -> Value {
+        json!({
+            "foo": 1,
+            "bar": "bar",
+            "baz": true
+        })
+    }
+
+    #[test]
+    fn and_rules() {
+        let map = get_test_data();
+        // Met & Met == Met
+        let mut root = and(vec![int_equals("foo", 1), string_equals("bar", "bar")]);
+        let mut res = root.check_value(&map);
+
+        assert!(res.status == Status::Met);
+
+        // Met & NotMet == NotMet
+        root = and(vec![int_equals("foo", 2), string_equals("bar", "bar")]);
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::NotMet);
+
+        // Met & Unknown == Unknown
+        root = and(vec![int_equals("quux", 2), string_equals("bar", "bar")]);
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::Unknown);
+
+        // NotMet & Unknown == NotMet
+        root = and(vec![int_equals("quux", 2), string_equals("bar", "baz")]);
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::NotMet);
+
+        // Unknown & Unknown == Unknown
+        root = and(vec![int_equals("quux", 2), string_equals("fizz", "bar")]);
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::Unknown);
+    }
+
+    #[test]
+    fn or_rules() {
+        let map = get_test_data();
+        // Met | Met == Met
+        let mut root = or(vec![int_equals("foo", 1), string_equals("bar", "bar")]);
+        let mut res = root.check_value(&map);
+
+        assert!(res.status == Status::Met);
+
+        // Met | NotMet == Met
+        root = or(vec![int_equals("foo", 2), string_equals("bar", "bar")]);
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::Met);
+
+        // Met | Unknown == Met
+        root = or(vec![int_equals("quux", 2), string_equals("bar", "bar")]);
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::Met);
+
+        // NotMet | Unknown == Unknown
+        root = or(vec![int_equals("quux", 2), string_equals("bar", "baz")]);
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::Unknown);
+
+        // Unknown | Unknown == Unknown
+        root = or(vec![int_equals("quux", 2), string_equals("fizz", "bar")]);
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::Unknown);
+    }
+
+    #[test]
+    fn not_rules() {
+        let map = get_test_data();
+        // !Met == NotMet
+        let mut root = not(int_equals("foo", 1));
+        let mut res = root.check_value(&map);
+
+        assert!(res.status == Status::NotMet);
+        assert!(res.children.len() == 1);
+        assert!(res.children[0].status == Status::Met);
+
+        // !NotMet == Met
+        root = not(int_equals("foo", 2));
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::Met);
+
+        // !Unknown == Unknown
+        root = not(int_equals("quux", 2));
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::Unknown);
+
+        // !(Met & NotMet) == Met
+        root = not(and(vec![int_equals("foo", 1), string_equals("bar", "baz")]));
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::Met);
+        assert!(res.children[0].children.len() == 2);
+    }
+
+    #[test]
+    fn not_rule_from_json() {
+        let map = get_test_data();
+        let root: Condition = serde_json::from_value(json!({
+            "not": {
+                "field": "bar",
+                "operator": "string_equals",
+                "value": "baz"
+            }
+        }))
+        .unwrap();
+        let res = root.check_value(&map);
+
+        assert!(res.status == Status::Met);
+        assert!(res.children[0].status == Status::NotMet);
+    }
+
+    #[test]
+    fn pascal_case_operators() {
+        let map = get_test_data();
+        let root: Condition = serde_json::from_value(json!({
+            "field": "foo",
+            "operator": "IntInRange",
+            "value": [0, 2]
+        }))
+        .unwrap();
+        let res = root.check_value(&map);
+
+        assert!(res.status == Status::Met);
+        assert!(serde_json::to_value(&root).unwrap()["operator"] == "int_in_range");
+
+        // Extra keys such as a description are ignored, as they always were
+        let root: Condition = serde_json::from_value(json!({
+            "field": "foo",
+            "operator": "IntEquals",
+            "value": 1,
+            "description": "note"
+        }))
+        .unwrap();
+        assert!(root.check_value(&map).status == Status::Met);
+
+        // The aliases apply to comparisons against another fact too
+        let root: Condition = serde_json::from_value(json!({
+            "field": "bar",
+            "operator": "StringEquals",
+            "value": {"fact": "bar"}
+        }))
+        .unwrap();
+        assert!(root.check_value(&map).status == Status::Met);
+        assert!(serde_json::to_value(&root).unwrap()["operator"] == "string_equals");
+
+        // Only the operators that predate snake_case names have aliases
+        assert!(serde_json::from_value::<Condition>(json!({
+            "field": "foo",
+            "operator": "IsNull"
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn n_of_rules() {
+        let map = get_test_data();
+        // 2 Met, 1 NotMet == Met
+        let mut root = at_least(
+            2,
+            vec![
+                int_equals("foo", 1),
+                string_equals("bar", "bar"),
+                bool_equals("baz", false),
+            ],
+        );
+        let mut res = root.check_value(&map);
+
+        assert!(res.status == Status::Met);
+
+        // 1 Met, 1 NotMet, 1 Unknown == Unknown
+        root = at_least(
+            2,
+            vec![
+                int_equals("foo", 1),
+                string_equals("quux", "bar"),
+                bool_equals("baz", false),
+            ],
+        );
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::Unknown);
+
+        // Legacy behaviour: 1 Met, 1 NotMet, 1 Unknown == NotMet
+        let options = CheckOptions {
+            legacy_at_least: true,
+            ..CheckOptions::default()
+        };
+        res = root.check_value_with_options(&map, &options);
+
+        assert!(res.status == Status::NotMet);
+
+        // 2 NotMet, 1 Unknown == NotMet
+        root = at_least(
+            2,
+            vec![
+                int_equals("foo", 2),
+                string_equals("quux", "baz"),
+                bool_equals("baz", false),
+            ],
+        );
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::NotMet);
+    }
+
+    #[test]
+    fn exactly_rules() {
+        let map = get_test_data();
+        // 1 Met, 2 NotMet == Met
+        let mut root = exactly(
+            1,
+            vec![
+                int_equals("foo", 1),
+                string_equals("bar", "baz"),
+                bool_equals("baz", false),
+            ],
+        );
+        let mut res = root.check_value(&map);
+
+        assert!(res.status == Status::Met);
+
+        // 2 Met, 1 NotMet == NotMet
+        root = exactly(
+            1,
+            vec![
+                int_equals("foo", 1),
+                string_equals("bar", "bar"),
+                bool_equals("baz", false),
+            ],
+        );
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::NotMet);
+
+        // 1 Met, 1 NotMet, 1 Unknown == Unknown
+        root = exactly(
+            1,
+            vec![
+                int_equals("foo", 1),
+                string_equals("quux", "bar"),
+                bool_equals("baz", false),
+            ],
+        );
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::Unknown);
+
+        // 2 NotMet, 1 Unknown == NotMet
+        root = exactly(
+            2,
+            vec![
+                int_equals("foo", 2),
+                string_equals("quux", "baz"),
+                bool_equals("baz", false),
+            ],
+        );
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::NotMet);
+    }
+
+    #[test]
+    fn at_most_rules() {
+        let map = get_test_data();
+        // 2 Met, 1 NotMet == Met
+        let mut root = at_most(
+            2,
+            vec![
+                int_equals("foo", 1),
+                string_equals("bar", "bar"),
+                bool_equals("baz", false),
+            ],
+        );
+        let mut res = root.check_value(&map);
+
+        assert!(res.status == Status::Met);
+
+        // 3 Met == NotMet
+        root = at_most(
+            2,
+            vec![
+                int_equals("foo", 1),
+                string_equals("bar", "bar"),
+                bool_equals("baz", true),
+            ],
+        );
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::NotMet);
+
+        // 2 Met, 1 Unknown == Unknown
+        root = at_most(
+            2,
+            vec![
+                int_equals("foo", 1),
+                string_equals("bar", "bar"),
+                bool_equals("quux", true),
+            ],
+        );
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::Unknown);
+
+        // 1 Met, 1 NotMet, 1 Unknown == Met
+        root = at_most(
+            2,
+            vec![
+                int_equals("foo", 1),
+                string_equals("bar", "baz"),
+                bool_equals("quux", true),
+            ],
+        );
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::Met);
+    }
+
+    #[test]
+    fn none_rules() {
+        let map = get_test_data();
+        // NotMet, NotMet == Met
+        let mut root = none(vec![int_equals("foo", 2), string_equals("bar", "baz")]);
+        let mut res = root.check_value(&map);
+
+        assert!(res.status == Status::Met);
+
+        // Met, Unknown == NotMet
+        root = none(vec![int_equals("foo", 1), string_equals("quux", "baz")]);
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::NotMet);
+
+        // NotMet, Unknown == Unknown
+        root = none(vec![int_equals("foo", 2), string_equals("quux", "baz")]);
+        res = root.check_value(&map);
+
+        assert!(res.status == Status::Unknown);
+    }
+
+    #[test]
+    fn quantifier_rules_from_json() {
+        let map = get_test_data();
+        let root: Condition = serde_json::from_value(json!({
+            "and": [
+                {
+                    "exactly": 1,
+                    "conditions": [
+                        { "field": "foo", "operator": "int_equals", "value": 1 },
+                        { "field": "bar", "operator": "string_equals", "value": "baz" }
+                    ]
+                },
+                {
+                    "at_most": 0,
+                    "conditions": [
+                        { "field": "baz", "operator": "bool_equals", "value": false }
+                    ]
+                },
+                {
+                    "none": [
+                        { "field": "foo", "operator": "int_equals", "value": 2 }
+                    ]
+                }
+            ]
+        }))
+        .unwrap();
+        let res = root.check_value(&map);
+
+        assert!(res.status == Status::Met);
+        assert!(res.children.iter().all(|c| c.status == Status::Met));
+    }
+
+    #[test]
+    fn field_comparison_rule() {
+        let map = json!({
+            "order": { "total": 120.5, "items": 3 },
+            "customer": { "credit_limit": 100, "max_items": 3, "name": "bar" },
+            "bar": "bar",
+            "baz": true
+        });
+        let mut rule: Condition = serde_json::from_value(json!({
+            "field": "/order/total",
+            "operator": "float_greater_than",
+            "value": { "fact": "/customer/credit_limit" }
+        }))
+        .unwrap();
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = field_comparison(
+            "order/items",
+            FactConstraint::IntLessThan("customer/max_items".into()),
+        );
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = field_comparison("bar", FactConstraint::StringEquals("customer/name".into()));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = field_comparison("baz", FactConstraint::BoolEquals("bar".into()));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        // Missing on either side should be Unknown
+        rule = field_comparison("quux", FactConstraint::IntEquals("order/items".into()));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Unknown);
+
+        rule = field_comparison("order/items", FactConstraint::IntEquals("quux".into()));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Unknown);
+
+        for operator in FactConstraint::OPERATORS {
+            rule = serde_json::from_value(json!({
+                "field": "bar",
+                "operator": operator,
+                "value": { "fact": "baz" }
+            }))
+            .unwrap();
+            assert!(matches!(rule, Condition::FieldComparison { .. }));
+        }
+
+        // Operators without a fact form say which ones have it
+        let err = serde_json::from_value::<Condition>(json!({
+            "field": "foo",
+            "operator": "int_in_range",
+            "value": { "fact": "baz" }
+        }))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("only supported by `string_equals`"));
+        assert!(err.contains("`bool_equals`"));
+    }
+
+    #[test]
+    fn array_element_rules() {
+        let map = json!({
+            "items": [
+                { "sku": "A-1", "price": 12.5, "qty": 1 },
+                { "sku": "B-2", "price": 3.0 },
+                { "sku": "C-3", "price": 150.0, "qty": 2 }
+            ],
+            "empty": [],
+            "bar": "bar"
+        });
+        let mut rule = any_element("items", float_greater_than("price", 100.0));
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+        assert!(res.children.len() == 3);
+
+        rule = all_elements("items", float_greater_than("price", 10.0));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = no_element("items", string_equals("sku", "D-4"));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // One element is missing `qty`
+        rule = all_elements("items", int_in_range("qty", 1, 5));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Unknown);
+
+        rule = any_element("empty", string_equals("sku", "A-1"));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = all_elements("empty", string_equals("sku", "A-1"));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // Missing field is Unknown, a field that isn't an array is NotMet
+        rule = any_element("quux", string_equals("sku", "A-1"));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Unknown);
+
+        rule = any_element("bar", string_equals("sku", "A-1"));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = serde_json::from_value(json!({
+            "field": "/items",
+            "any": {
+                "and": [
+                    { "field": "/sku", "operator": "string_starts_with", "value": "C-" },
+                    { "field": "/qty", "operator": "int_greater_than", "value": 1 }
+                ]
+            }
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = serde_json::from_value(json!({
+            "field": "items",
+            "none": { "field": "price", "operator": "float_less_than", "value": 1.0 }
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+    }
+
+    #[test]
+    fn existence_and_type_rules() {
+        let mut map = get_test_data();
+        map["nothing".to_owned()] = json!(null);
+        map["list".to_owned()] = json!([1, 2]);
+
+        let mut rule = exists("foo");
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = exists("quux");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = not_exists("quux");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = not_exists("nothing");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = is_null("nothing");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = is_null("quux");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = is_not_null("bar");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = is_not_null("quux");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = is_type("foo", JsonType::Integer);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // A whole float isn't an integer, the same as for the int operators
+        rule = is_type("whole", JsonType::Integer);
+        res = rule.check_value(&json!({"whole": 5.0}));
+        assert!(res.status == Status::NotMet);
+
+        rule = is_type("list", JsonType::Object);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = is_type("quux", JsonType::String);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = serde_json::from_value(json!({
+            "and": [
+                { "field": "bar", "operator": "exists" },
+                { "field": "list", "operator": "is_type", "value": "array" },
+                { "field": "quux", "operator": "not_exists" }
+            ]
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+    }
+
+    #[test]
+    fn length_rules() {
+        let map = json!({
+            "comment": "  h\u{e9}llo ",
+            "tags": ["a", "b", "c"],
+            "payload": { "a": 1, "b": 2 },
+            "foo": 1
+        });
+        // Strings are measured in chars, not bytes
+        let mut rule = length_equals("comment", 8);
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = with_modifiers(
+            length_equals("comment", 5),
+            Modifiers {
+                trim: true,
+                ..Modifiers::default()
+            },
+        );
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = length_greater_than("tags", 2);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = length_less_than("payload", 2);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = length_in_range("payload", 1, 10);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // Values without a length should be NotMet
+        rule = length_equals("foo", 1);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = serde_json::from_value(json!({
+            "field": "tags",
+            "operator": "length_in_range",
+            "value": [3, 5]
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+    }
+
+    #[test]
+    fn expression_rules() {
+        let map = json!({
+            "price": 250.5,
+            "quantity": 4,
+            "slot": { "start": 1000, "end": 4000 },
+            "bar": "bar"
+        });
+        let mut rule =
+            expression("price * quantity", Constraint::FloatGreaterThan(1000.0)).unwrap();
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+        assert!(res.name == "(price * quantity)");
+
+        rule = expression(
+            "/slot/end - /slot/start",
+            Constraint::IntLessThan(3600.into()),
+        )
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = expression(
+            "round(max(price, 100) / 3) % 7 + abs(-quantity)",
+            Constraint::IntEquals(4.into()),
+        )
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // Missing operands are Unknown
+        rule = expression("price * discount", Constraint::FloatLessThan(10.0)).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Unknown);
+
+        // Division by zero has no value, non-numeric operands evaluate to null
+        rule = expression("price / (quantity - 4)", Constraint::FloatGreaterThan(0.0)).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Unknown);
+        assert!(res.reason == Some(Reason::DivisionByZero));
+
+        rule = expression("price % 0", Constraint::IsNull).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Unknown);
+        assert!(res.reason == Some(Reason::DivisionByZero));
+
+        rule = expression("bar + 1", Constraint::IsNull).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // u64 operands and results stay exact
+        let big = json!({"id": u64::MAX - 1, "low": i64::MIN});
+        rule = expression("id + 1", Constraint::IntEquals(u64::MAX.into())).unwrap();
+        res = rule.check_value(&big);
+        assert!(res.status == Status::Met);
+
+        rule = expression("id - 18446744073709551613", Constraint::IntEquals(1.into())).unwrap();
+        res = rule.check_value(&big);
+        assert!(res.status == Status::Met);
+
+        rule = expression("id + low", Constraint::IntEquals((i64::MAX - 1).into())).unwrap();
+        res = rule.check_value(&big);
+        assert!(res.status == Status::Met);
+
+        // Non-ASCII whitespace separates tokens like a plain space
+        rule = expression("price\u{a0}* 2", Constraint::FloatEquals(501.0)).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        assert!(expression("price *", Constraint::IsNull).is_err());
+        assert!(expression("sqrt(price)", Constraint::IsNull).is_err());
+        assert!(expression("(price", Constraint::IsNull).is_err());
+
+        rule = serde_json::from_value(json!({
+            "expr": { "multiply": [{ "fact": "/price" }, { "fact": "quantity" }, 2] },
+            "operator": "float_in_range",
+            "value": [2000, 2010]
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = serde_json::from_value(json!({
+            "expr": "(/slot/end - /slot/start) / 60",
+            "operator": "int_equals",
+            "value": 50
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = serde_json::from_value(json!({
+            "expr": "quantity\u{3000}+ 1",
+            "operator": "int_equals",
+            "value": 5
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        assert!(serde_json::from_value::<Condition>(json!({
+            "expr": "price ** 2",
+            "operator": "int_equals",
+            "value": 50
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn string_equals_rule() {
+        let map = get_test_data();
+        let mut rule = string_equals("bar", "bar");
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = string_equals("bar", "baz");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+    }
+
+    #[test]
+    fn string_pattern_rules() {
+        let map = json!({
+            "email": "john.doe@example.com",
+            "host": "api.eu.example.com",
+            "foo": 1
+        });
+        let mut rule = string_matches("email", r"^[a-z.]+@example\.com$").unwrap();
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = string_matches("email", r"^\d+$").unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = string_starts_with("email", "john");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = string_ends_with("email", "@example.org");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = string_includes_substring("email", ".doe@");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = glob("host", "*.example.com").unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = glob("host", "api.??.example.org").unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        // Values that aren't strings should be NotMet
+        rule = string_starts_with("foo", "1");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        assert!(string_matches("email", "(unclosed").is_err());
+        assert!(glob("host", "[unclosed").is_err());
+    }
+
+    #[test]
+    fn string_modifiers_rules() {
+        let map = json!({
+            "name": "  John Doe ",
+            "city": "Zu\u{308}rich",
+            "tags": ["VIP", "Beta"]
+        });
+        let mut rule = string_equals_ignore_case("name", "  john doe ");
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = string_equals_ignore_case("name", "john doe");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        let modifiers = Modifiers {
+            case_insensitive: true,
+            normalize: Some(Normalization::Nfkc),
+            trim: true,
+            ..Modifiers::default()
+        };
+
+        rule = with_modifiers(string_equals("name", "JOHN DOE"), modifiers.clone());
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // NFD fact vs NFC literal
+        rule = string_equals("city", "Z\u{fc}rich");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = with_modifiers(string_in("city", vec!["z\u{fc}rich"]), modifiers.clone());
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = string_in_ignore_case("name", vec!["  JOHN DOE "]);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = with_modifiers(
+            and(vec![
+                string_matches("name", "^John").unwrap(),
+                glob("name", "*DOE").unwrap(),
+            ]),
+            modifiers,
+        );
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = serde_json::from_value(json!({
+            "field": "tags",
+            "operator": "string_contains",
+            "value": "vip",
+            "case_insensitive": true
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = serde_json::from_value(json!({
+            "field": "name",
+            "operator": "string_starts_with",
+            "value": "JOHN",
+            "trim": true,
+            "normalize": "nfc"
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        // Lowercasing, not case folding
+        rule = string_equals_ignore_case("city", "ZURICH");
+        res = rule.check_value(&json!({ "city": "zurich" }));
+        assert!(res.status == Status::Met);
+
+        rule = string_equals_ignore_case("city", "STRASSE");
+        res = rule.check_value(&json!({ "city": "stra\u{df}e" }));
+        assert!(res.status == Status::NotMet);
+    }
+
+    #[test]
+    fn invalid_pattern_rejected_on_load() {
+        let err = serde_json::from_value::<Rule>(json!({
+            "conditions": {
+                "and": [{
+                    "field": "email",
+                    "operator": "string_matches",
+                    "value": "(unclosed"
+                }]
+            },
+            "event": {
+                "type": "message",
+                "params": { "type": "info", "title": "title", "message": "message" }
+            }
+        }))
+        .unwrap_err();
+
+        assert!(err.to_string().contains("unclosed group"));
+    }
+
+    #[test]
+    fn date_rules() {
+        let map = json!({
+            "created_at": "2020-11-14T23:30:00+01:00",
+            "updated_at": 1605403800,
+            "bar": "bar"
+        });
+        let options = CheckOptions {
+            now: Some(Utc.with_ymd_and_hms(2020, 11, 20, 12, 0, 0).unwrap()),
+            ..CheckOptions::default()
+        };
+        let nov_14 = Utc.with_ymd_and_hms(2020, 11, 14, 0, 0, 0).unwrap();
+        let nov_15 = Utc.with_ymd_and_hms(2020, 11, 15, 0, 0, 0).unwrap();
+
+        let mut rule = date_after("created_at", nov_14);
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = date_before("updated_at", nov_15);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = date_between("created_at", nov_14, nov_15);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = within_last("created_at", Duration::days(7));
+        res = rule.check_value_with_options(&map, &options);
+        assert!(res.status == Status::Met);
+
+        rule = within_last("created_at", Duration::days(5));
+        res = rule.check_value_with_options(&map, &options);
+        assert!(res.status == Status::NotMet);
+
+        // 22:30 UTC on a Saturday, already Sunday in Tokyo
+        rule = day_of_week_in("created_at", vec![Weekday::Sat], chrono_tz::UTC);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = day_of_week_in("created_at", vec![Weekday::Sat], chrono_tz::Asia::Tokyo);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        // 22:30 UTC is 17:30 in New York
+        rule = time_of_day_between(
+            "created_at",
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            chrono_tz::America::New_York,
+        );
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = time_of_day_between(
+            "created_at",
+            NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+            chrono_tz::UTC,
+        );
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // Fractional epochs before 1970, and ones that round up to a full second
+        let epochs = json!({"before": -1.5, "almost": 0.9999999999});
+        rule = date_between(
+            "before",
+            Utc.timestamp_millis_opt(-1501).unwrap(),
+            Utc.timestamp_millis_opt(-1499).unwrap(),
+        );
+        res = rule.check_value(&epochs);
+        assert!(res.status == Status::Met);
+
+        rule = date_after("almost", Utc.timestamp_opt(0, 0).unwrap());
+        res = rule.check_value(&epochs);
+        assert!(res.status == Status::Met);
+        assert!(res.reason.is_none());
+
+        // Values not convertible to a date should be NotMet
+        rule = date_after("bar", nov_14);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+    }
+
+    #[test]
+    fn date_rules_from_json() {
+        let map = json!({ "signed_up": "2020-10-01T08:00:00Z" });
+        let options = CheckOptions {
+            now: Some(Utc.with_ymd_and_hms(2020, 11, 20, 12, 0, 0).unwrap()),
+            ..CheckOptions::default()
+        };
+        let rule: Condition = serde_json::from_value(json!({
+            "and": [
+                { "field": "signed_up", "operator": "date_before", "value": 1602000000 },
+                { "field": "signed_up", "operator": "within_last", "value": "8w" },
+                {
+                    "field": "signed_up",
+                    "operator": "day_of_week_in",
+                    "value": { "days": ["thu", "fri"], "timezone": "Europe/Paris" }
+                },
+                {
+                    "field": "signed_up",
+                    "operator": "time_of_day_between",
+                    "value": { "start": "09:00:00", "end": "17:00:00", "timezone": "Europe/Paris" }
+                }
+            ]
+        }))
+        .unwrap();
+        let res = rule.check_value_with_options(&map, &options);
+        assert!(res.status == Status::Met);
+
+        assert!(serde_json::from_value::<Condition>(json!({
+            "field": "signed_up",
+            "operator": "within_last",
+            "value": "30 days"
+        }))
+        .is_err());
+        assert!(serde_json::from_value::<Condition>(json!({
+            "field": "signed_up",
+            "operator": "day_of_week_in",
+            "value": { "days": ["sat"], "timezone": "Mars/Olympus_Mons" }
+        }))
+        .is_err());
+
+        // Spans too long to represent fail to load instead of panicking
+        for span in &[
+            json!("99999999999999w"),
+            json!(9223372036854775807_i64),
+            json!("9000000000000000s9000000000000000s"),
+        ] {
+            assert!(serde_json::from_value::<Condition>(json!({
+                "field": "signed_up",
+                "operator": "within_last",
+                "value": span
+            }))
+            .is_err());
+        }
+
+        // Negative spans are rejected rather than matching dates in the future
+        for span in &[json!(-5), json!("-5d")] {
+            let err = serde_json::from_value::<Condition>(json!({
+                "field": "signed_up",
+                "operator": "within_last",
+                "value": span
+            }))
+            .unwrap_err();
+            assert!(err.to_string().contains("is negative"));
+        }
+
+        // A span reaching before the earliest date covers everything up to now
+        let rule: Condition = serde_json::from_value(json!({
+            "field": "signed_up",
+            "operator": "within_last",
+            "value": "100000000d"
+        }))
+        .unwrap();
+        let res = rule.check_value_with_options(&map, &options);
+        assert!(res.status == Status::Met);
+    }
+
+    #[test]
+    fn int_equals_rule() {
+        let map = get_test_data();
+        let mut rule = int_equals("foo", 1);
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = int_equals("foo", 2);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        // Values not convertible to int should be NotMet
+        rule = int_equals("bar", 2);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+    }
+
+    #[test]
+    fn int_range_rule() {
+        let map = get_test_data();
+        let mut rule = int_in_range("foo", 1, 3);
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = int_in_range("foo", 2, 3);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        // Values not convertible to int should be NotMet
+        rule = int_in_range("bar", 1, 3);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+    }
+
+    #[test]
+    fn decimal_rules() {
+        let map = json!({
+            "subtotal": 0.1,
+            "tax": "0.2",
+            "total": "0.30",
+            "float_total": 0.30000000000000004,
+            "bar": "bar"
+        });
+        let cents = |n: i64| Decimal::new(n, 2);
+
+        let mut rule = decimal_equals("total", cents(30));
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = decimal_equals("float_total", cents(30));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = decimal_equals("subtotal", Decimal::new(1, 1));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = decimal_in("tax", vec![cents(10), cents(20)]);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = decimal_in_range("total", cents(10), cents(30));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = decimal_greater_than("total", cents(30));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = decimal_greater_than_inclusive("total", cents(30));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // Values not convertible to a decimal should be NotMet
+        rule = decimal_not_equals("bar", cents(30));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = serde_json::from_value(json!({
+            "field": "total",
+            "operator": "decimal_less_than",
+            "value": "0.31"
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = serde_json::from_value(json!({
+            "field": "subtotal",
+            "operator": "decimal_equals",
+            "value": 0.1
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        assert!(serde_json::from_value::<Condition>(json!({
+            "field": "total",
+            "operator": "decimal_equals",
+            "value": "ten"
+        }))
+        .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "arbitrary_precision")]
+    fn decimal_precision_rules() {
+        // Parsed from text, as an `f64` these would be `0.3` and `12345678901234568`
+        let map: Value = serde_json::from_str(
+            r#"{"almost": 0.30000000000000001, "large": 12345678901234567.89}"#,
+        )
+        .unwrap();
+
+        let mut rule = decimal_equals("almost", Decimal::new(3, 1));
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = serde_json::from_value(json!({
+            "field": "large",
+            "operator": "decimal_equals",
+            "value": "12345678901234567.89"
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+    }
+
+    #[test]
+    #[cfg(not(feature = "arbitrary_precision"))]
+    fn decimal_string_rules() {
+        // Without `arbitrary_precision`, exact values are given as strings
+        let map: Value = serde_json::from_str(
+            r#"{"almost": "0.30000000000000001", "rounded": 0.30000000000000001, "large": "12345678901234567.89"}"#,
+        )
+        .unwrap();
+
+        let mut rule = decimal_equals("almost", Decimal::new(3, 1));
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = decimal_equals("rounded", Decimal::new(3, 1));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = decimal_equals("large", "12345678901234567.89".parse().unwrap());
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+    }
+
+    #[test]
+    #[cfg(not(feature = "arbitrary_precision"))]
+    fn big_literal_rules() {
+        // Integer literals beyond u64 load as floats, and as strings for int operators
+        let mut rule: Condition = serde_json::from_str(
+            r#"{"field": "big", "operator": "float_greater_than", "value": 100000000000000000000}"#,
+        )
+        .unwrap();
+        let mut res = rule.check_value(&json!({"big": 1e21}));
+        assert!(res.status == Status::Met);
+
+        rule = serde_json::from_str(
+            r#"{"field": "big", "operator": "int_less_than", "value": "100000000000000000000"}"#,
+        )
+        .unwrap();
+        res = rule.check_value(&json!({"big": u64::MAX}));
+        assert!(res.status == Status::Met);
+    }
+
+    #[test]
+    #[cfg(feature = "arbitrary_precision")]
+    fn big_int_fact_rules() {
+        // Facts beyond u64 are read from their digits
+        let map: Value = serde_json::from_str(
+            r#"{"big": 100000000000000000001, "huge": -1000000000000000000000000000000000000000001}"#,
+        )
+        .unwrap();
+
+        let mut rule = serde_json::from_value::<Condition>(json!({
+            "field": "big",
+            "operator": "int_equals",
+            "value": "100000000000000000001"
+        }))
+        .unwrap();
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = int_greater_than("big", u64::MAX);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = serde_json::from_value::<Condition>(json!({
+            "field": "huge",
+            "operator": "int_less_than",
+            "value": "-1000000000000000000000000000000000000000000"
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+    }
+
+    #[test]
+    fn big_int_rules() {
+        let map = json!({
+            "id": 18_446_744_073_709_551_615u64,
+            "ids": [1, 9_223_372_036_854_775_808u64],
+            "counter": 18_446_744_073_709_551_616.0,
+        });
+
+        let mut rule = int_equals("id", u64::MAX);
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = int_greater_than("id", i64::MAX);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = int_contains("ids", 9_223_372_036_854_775_808u64);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // Literals beyond u64 are given as strings
+        rule = serde_json::from_value::<Condition>(json!({
+            "field": "id",
+            "operator": "int_less_than",
+            "value": "100000000000000000000000000000"
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        assert!(serde_json::from_value::<Condition>(json!({
+            "field": "id",
+            "operator": "int_less_than",
+            "value": 1e30
+        }))
+        .is_err());
+
+        // A number too large to be read exactly is out of range, not a mismatch
+        rule = int_greater_than("counter", 0);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+        assert!(res.reason == Some(Reason::OutOfRange));
+
+        rule = int_equals("id", 0);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+        assert!(res.reason.is_none());
+
+        rule = int_equals("big_float", 0);
+        res = rule.check_value(&json!({"big_float": 1e20}));
+        assert!(res.status == Status::NotMet);
+        assert!(res.reason == Some(Reason::OutOfRange));
+
+        // A whole float within range is a plain type mismatch
+        rule = int_equals("float", 5);
+        res = rule.check_value(&json!({"float": 5.0}));
+        assert!(res.status == Status::NotMet);
+        assert!(res.reason == Some(Reason::TypeMismatch));
+
+        rule = int_contains("floats", 2);
+        res = rule.check_value(&json!({"floats": [1.0, 2]}));
+        assert!(res.status == Status::Met);
+
+        rule = int_contains("floats", 3);
+        res = rule.check_value(&json!({"floats": [1.0, 2]}));
+        assert!(res.status == Status::NotMet);
+        assert!(res.reason.is_none());
+
+        let err = serde_json::from_value::<Condition>(json!({
+            "field": "id",
+            "operator": "int_equals",
+            "value": 5.0
+        }))
+        .unwrap_err();
+        assert!(err.to_string().contains("invalid integer"));
+    }
+
+    #[test]
+    fn semver_rules() {
+        let map = json!({
+            "app_version": "2.14.3-beta.1",
+            "os_version": "v17.0.1",
+            "build": 42
+        });
+
+        let mut rule = semver_greater_than("app_version", "2.14.2").unwrap();
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // A pre-release is lower than its release
+        rule = semver_less_than("app_version", "2.14.3").unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = semver_equals("os_version", "17.0.1").unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = semver_in_range("os_version", "16.0.0", "17.0.0").unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = semver_satisfies("os_version", ">=17.0, <18").unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = semver_satisfies("app_version", ">=2.1, <3").unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = semver_satisfies("build", ">=1").unwrap();
+        res = rule.check_value(&map);
+        assert!(res.reason == Some(Reason::TypeMismatch));
+
+        assert!(semver_satisfies("app_version", ">=2.1 <<3").is_err());
+        assert!(serde_json::from_value::<Condition>(json!({
+            "field": "app_version",
+            "operator": "semver_satisfies",
+            "value": "not a requirement"
+        }))
+        .is_err());
+
+        rule = serde_json::from_value::<Condition>(json!({
+            "field": "app_version",
+            "operator": "semver_in_range",
+            "value": ["2.0.0", "3.0.0"]
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+    }
+
+    #[test]
+    fn ip_rules() {
+        let map = json!({
+            "ip": "10.1.2.3",
+            "mapped": "::ffff:192.168.0.10",
+            "ipv6": "2001:db8::1",
+            "public": "8.8.8.8",
+            "port": 443
+        });
+
+        let mut rule = ip_in_cidr("ip", vec!["192.168.0.0/16", "10.0.0.0/8"]).unwrap();
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = ip_in_cidr("mapped", vec!["192.168.0.0/24"]).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = ip_in_cidr("ipv6", vec!["10.0.0.0/8", "2001:db8::/32"]).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = ip_not_in_cidr("public", vec!["8.8.4.0/24", "0.0.0.0/5"]).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = ip_equals("mapped", "192.168.0.10").unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = ip_is_private("ip");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = ip_is_private("public");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = ip_is_private("port");
+        res = rule.check_value(&map);
+        assert!(res.reason == Some(Reason::TypeMismatch));
+
+        // IPv4-mapped blocks, with an IPv4 form from a /96 prefix on
+        rule = ip_in_cidr("ip", vec!["::ffff:0:0/96"]).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+        assert!(serde_json::to_value(&rule).unwrap()["value"] == json!(["0.0.0.0/0"]));
+
+        rule = ip_in_cidr("mapped", vec!["::ffff:192.168.0.0/120"]).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = ip_in_cidr("ip", vec!["::ffff:0:0/95", "::/0"]).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+        assert!(
+            serde_json::to_value(&rule).unwrap()["value"] == json!(["::ffff:0.0.0.0/95", "::/0"])
+        );
+
+        rule = ip_not_in_cidr("ip", vec!["::/80", "2001:db8::/32"]).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = ip_in_cidr("ip", vec!["2001:db8::/32", "::ffff:0:0/112"]).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        assert!(ip_in_cidr("ip", vec!["10.0.0.0/33"]).is_err());
+        assert!(ip_equals("ip", "10.0.0").is_err());
+
+        rule = serde_json::from_value::<Condition>(json!({
+            "field": "ipv6",
+            "operator": "ip_in_cidr",
+            "value": "2001:db8::/127"
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        assert!(serde_json::from_value::<Condition>(json!({
+            "field": "ip",
+            "operator": "ip_not_in_cidr",
+            "value": ["10.0.0.0/8", "nope"]
+        }))
+        .is_err());
     }
 
     #[test]
-    fn int_equals_rule() {
-        let map = get_test_data();
-        let mut rule = int_equals("foo", 1);
+    fn geo_rules() {
+        let map = json!({
+            // Distances below are from Notre-Dame, at 48.853, 2.3499
+            "eiffel": {"lat": 48.8584, "lon": 2.2945},
+            "louvre": {"lat": 48.8606, "lon": 2.3376},
+            "london": {"lat": 51.5074, "lon": -0.1278},
+            "address": "Champ de Mars"
+        });
+        let area = json!({
+            "type": "Polygon",
+            "coordinates": [
+                [[2.25, 48.81], [2.42, 48.81], [2.42, 48.91], [2.25, 48.91], [2.25, 48.81]],
+                [[2.33, 48.85], [2.35, 48.85], [2.35, 48.87], [2.33, 48.87], [2.33, 48.85]]
+            ]
+        });
+
+        let mut rule = geo_within_radius("louvre", 48.853, 2.3499, 2.0).unwrap();
         let mut res = rule.check_value(&map);
         assert!(res.status == Status::Met);
 
-        rule = int_equals("foo", 2);
+        rule = geo_within_radius("eiffel", 48.853, 2.3499, 2.0).unwrap();
         res = rule.check_value(&map);
         assert!(res.status == Status::NotMet);
 
-        // Values not convertible to int should be NotMet
-        rule = int_equals("bar", 2);
+        rule = geo_within_radius("london", 48.853, 2.3499, 350.0).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        assert!(geo_within_radius("eiffel", 91.0, 0.0, 1.0).is_err());
+        assert!(geo_within_radius("eiffel", 0.0, 180.5, 1.0).is_err());
+        assert!(geo_within_radius("eiffel", 0.0, 0.0, -1.0).is_err());
+        assert!(geo_within_radius("eiffel", 0.0, 0.0, f64::NAN).is_err());
+        assert!(geo_within_radius("eiffel", 0.0, 0.0, f64::INFINITY).is_err());
+
+        rule = geo_in_polygon("eiffel", area.clone()).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // Inside the hole
+        rule = geo_in_polygon("louvre", area.clone()).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = geo_in_polygon("london", area).unwrap();
         res = rule.check_value(&map);
         assert!(res.status == Status::NotMet);
+
+        rule = geo_in_polygon(
+            "address",
+            json!({
+                "type": "MultiPolygon",
+                "coordinates": [[[[0, 0], [1, 0], [1, 1], [0, 0]]]]
+            }),
+        )
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.reason == Some(Reason::TypeMismatch));
+
+        // Rings must be closed
+        assert!(geo_in_polygon(
+            "eiffel",
+            json!({
+                "type": "Polygon",
+                "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 1]]]
+            })
+        )
+        .is_err());
+
+        rule = serde_json::from_value::<Condition>(json!({
+            "field": "london",
+            "operator": "geo_within_radius",
+            "value": {"lat": 51.5, "lon": -0.12, "radius_km": 1}
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        assert!(serde_json::from_value::<Condition>(json!({
+            "field": "london",
+            "operator": "geo_within_radius",
+            "value": {"lat": 91, "lon": 0, "radius_km": 1}
+        }))
+        .is_err());
     }
 
     #[test]
-    fn int_range_rule() {
-        let map = get_test_data();
-        let mut rule = int_in_range("foo", 1, 3);
+    fn similarity_rules() {
+        let map = json!({"name": "Jon Smyth", "other": "Jane Doe"});
+
+        let mut rule = string_similar(
+            "name",
+            "John Smith",
+            Similarity::Levenshtein { max_distance: 2 },
+        )
+        .unwrap();
         let mut res = rule.check_value(&map);
         assert!(res.status == Status::Met);
 
-        rule = int_in_range("foo", 2, 3);
+        rule = string_similar(
+            "other",
+            "John Smith",
+            Similarity::Levenshtein { max_distance: 2 },
+        )
+        .unwrap();
         res = rule.check_value(&map);
         assert!(res.status == Status::NotMet);
 
-        // Values not convertible to int should be NotMet
-        rule = int_in_range("bar", 1, 3);
+        rule = string_similar(
+            "name",
+            "John Smith",
+            Similarity::JaroWinkler {
+                min_similarity: 0.9,
+            },
+        )
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = string_similar(
+            "name",
+            "john smith",
+            Similarity::NormalizedLevenshtein {
+                min_similarity: 0.8,
+            },
+        )
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = with_modifiers(
+            rule,
+            Modifiers {
+                case_insensitive: true,
+                ..Modifiers::default()
+            },
+        );
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        assert!(string_similar(
+            "name",
+            "John Smith",
+            Similarity::JaroWinkler {
+                min_similarity: 1.5,
+            },
+        )
+        .is_err());
+        assert!(string_similar(
+            "name",
+            "John Smith",
+            Similarity::NormalizedLevenshtein {
+                min_similarity: f64::NAN,
+            },
+        )
+        .is_err());
+
+        rule = serde_json::from_value::<Condition>(json!({
+            "field": "other",
+            "operator": "string_similar",
+            "value": {"target": "Jane Do", "algorithm": "levenshtein", "max_distance": 1}
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        assert!(serde_json::from_value::<Condition>(json!({
+            "field": "other",
+            "operator": "string_similar",
+            "value": {"target": "Jane Do", "algorithm": "jaro_winkler", "min_similarity": -0.1}
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn set_rules() {
+        let map = json!({
+            "tags": ["red", "Green", "blue"],
+            "sizes": [1, 2.0, 3],
+            "prices": [9.5, 10.25],
+            "empty": [],
+            "name": "red"
+        });
+
+        let mut rule = contains_all("tags", vec!["red", "blue"]);
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = contains_all("tags", vec!["red", "green"]);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = with_modifiers(
+            contains_all("tags", vec!["red", "green"]),
+            Modifiers {
+                case_insensitive: true,
+                ..Modifiers::default()
+            },
+        );
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = contains_any("sizes", vec![2, 7]);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = contains_none("prices", vec![10.0, 11.0]);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = subset_of("sizes", vec![1, 2, 3, 4]);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = subset_of("empty", vec![1]);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = superset_of("sizes", vec![1, 2, 3, 4]);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = intersects("prices", vec![10.25]);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = array_equals("sizes", vec![1, 2, 3]);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = array_equals("sizes", vec![3, 2, 1]);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = array_equals_unordered("sizes", vec![3, 2, 1]);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = array_equals_unordered("sizes", vec![3, 3, 1]);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = contains_any("name", vec!["red"]);
+        res = rule.check_value(&map);
+        assert!(res.reason == Some(Reason::TypeMismatch));
+
+        rule = serde_json::from_value::<Condition>(json!({
+            "field": "tags",
+            "operator": "contains_all",
+            "value": ["blue", "red"]
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+    }
+
+    #[test]
+    fn value_rules() {
+        let map = json!({
+            "customer": {
+                "name": "Jane",
+                "address": {"city": "Paris", "zip": "75001"},
+                "tags": ["vip", "early"],
+                "score": 10
+            },
+            "status": null
+        });
+
+        let mut rule = equals("customer/address", json!({"zip": "75001", "city": "Paris"}));
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = equals("customer/address", json!({"city": "Paris"}));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = equals("customer/score", 10.0);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = not_equals("status", json!("active"));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = is_in("status", vec![json!(null), json!("inactive")]);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = is_in(
+            "customer/tags",
+            vec![json!(["vip"]), json!(["vip", "early"])],
+        );
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = matches_subset(
+            "customer",
+            json!({"address": {"city": "Paris"}, "tags": ["early"]}),
+        );
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = matches_subset("customer", json!({"address": {"city": "Lyon"}}));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = matches_subset("customer", json!({"phone": null}));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = serde_json::from_value::<Condition>(json!({
+            "field": "customer",
+            "operator": "matches_subset",
+            "value": {"name": "jane"},
+            "case_insensitive": true
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = serde_json::from_value::<Condition>(json!({
+            "field": "customer/score",
+            "operator": "in",
+            "value": [1, 10]
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // Objects with a `fact` key are literals for operators without a fact form
+        let map = json!({"ref": {"fact": "y", "source": "crm"}, "y": 1});
+        rule = serde_json::from_value::<Condition>(json!({
+            "field": "ref",
+            "operator": "equals",
+            "value": {"fact": "y", "source": "crm"}
+        }))
+        .unwrap();
         res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = serde_json::from_value::<Condition>(json!({
+            "field": "ref",
+            "operator": "matches_subset",
+            "value": {"fact": "y"}
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+    }
+
+    #[test]
+    fn selector_rules() {
+        let map = json!({
+            "items": [
+                {"sku": "a", "price": 120.0},
+                {"sku": "b", "price": 15.5}
+            ],
+            "addresses": {
+                "home": {"country": "FR"},
+                "work": {"country": "FR"}
+            },
+            "empty": []
+        });
+
+        let mut rule = select_any("/items/*/price", Constraint::FloatGreaterThan(100.0)).unwrap();
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+        assert!(res.children.len() == 2);
+        assert!(res.children[0].name == "/items/0/price");
+
+        rule = select_all("items/*/price", Constraint::FloatGreaterThan(100.0)).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        rule = select_all(
+            "$.addresses.*.country",
+            Constraint::StringEquals("FR".into()),
+        )
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = select_any("$..sku", Constraint::StringEquals("b".into())).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = select_all("$['items'][1].sku", Constraint::StringEquals("b".into())).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // An empty array selects nothing, which like all_elements is vacuously Met for all
+        rule = select_any("/empty/*/price", Constraint::FloatGreaterThan(0.0)).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+        assert!(rule.check_status(&map) == Status::NotMet);
+
+        rule = select_all("/empty/*", Constraint::FloatGreaterThan(0.0)).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+        assert!(rule.check_status(&map) == Status::Met);
+
+        rule = all_elements("empty", float_greater_than("price", 0.0));
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // A missing field is still Unknown
+        rule = select_all("/missing/*", Constraint::FloatGreaterThan(0.0)).unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Unknown);
+        assert!(rule.check_status(&map) == Status::Unknown);
+
+        // A branch missing the rest of the selector counts as a missing field
+        let partial = json!({"items": [{"price": 20}, {"sku": "a"}]});
+        rule = select_all("/items/*/price", Constraint::FloatGreaterThan(10.0)).unwrap();
+        res = rule.check_value(&partial);
+        assert!(res.status == Status::Unknown);
+        assert!(res.children[1].name == "/items/1");
+        assert!(rule.check_status(&partial) == Status::Unknown);
+
+        rule = select_any("/items/*/price", Constraint::FloatGreaterThan(10.0)).unwrap();
+        res = rule.check_value(&partial);
+        assert!(res.status == Status::Met);
+
+        rule = select_all("$..items[*].price", Constraint::FloatGreaterThan(10.0)).unwrap();
+        res = rule.check_value(&partial);
+        assert!(res.status == Status::Unknown);
+
+        rule = select_all("/items/*/price", Constraint::FloatGreaterThan(30.0)).unwrap();
+        res = rule.check_value(&partial);
         assert!(res.status == Status::NotMet);
+
+        assert!(select_any("$.items[?]", Constraint::Exists).is_err());
+
+        rule = serde_json::from_value::<Condition>(json!({
+            "select": "$.items[*].sku",
+            "quantifier": "all",
+            "operator": "string_in",
+            "value": ["a", "b"]
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        // The quantifier is required
+        assert!(serde_json::from_value::<Condition>(json!({
+            "select": "/items/*/sku",
+            "operator": "string_equals",
+            "value": "a"
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn path_dialect_rules() {
+        let map = json!({
+            "customer": {"address": {"city": "Paris"}},
+            "customer.address.city": "Lyon",
+            "items": [{"sku": "a"}, {"sku": "b", "tags": ["x", "y"]}],
+            "a.b": {"c[0]": 1}
+        });
+        let dot = CheckOptions {
+            path_dialect: PathDialect::Dot,
+            ..CheckOptions::default()
+        };
+
+        // By default the field is a single key
+        let mut rule = string_equals("customer.address.city", "Lyon");
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = string_equals("customer.address.city", "Paris");
+        res = rule.check_value_with_options(&map, &dot);
+        assert!(res.status == Status::Met);
+
+        rule = string_equals("items[1].sku", "b");
+        res = rule.check_value_with_options(&map, &dot);
+        assert!(res.status == Status::Met);
+
+        rule = string_equals("items[1].tags[0]", "x");
+        res = rule.check_value_with_options(&map, &dot);
+        assert!(res.status == Status::Met);
+
+        rule = int_equals("a\\.b.c\\[0]", 1);
+        res = rule.check_value_with_options(&map, &dot);
+        assert!(res.status == Status::Met);
+
+        // Pointers still work
+        rule = string_equals("/customer/address/city", "Paris");
+        res = rule.check_value_with_options(&map, &dot);
+        assert!(res.status == Status::Met);
+
+        let mut rule = serde_json::from_value::<Rule>(json!({
+            "conditions": {"field": "customer.address.city", "operator": "string_equals", "value": "Paris"},
+            "event": {
+                "type": "message",
+                "params": {"type": "info", "title": "paris", "message": "paris"}
+            },
+            "path_dialect": "dot"
+        }))
+        .unwrap();
+        let res = rule.check_value(&map);
+        assert!(res.condition_result.status == Status::Met);
+
+        rule.set_path_dialect(PathDialect::Pointer);
+        let res = rule.check_value_with_options(&map, &dot);
+        assert!(res.condition_result.status == Status::NotMet);
+
+        // Expression facts follow the dialect too
+        let map = json!({"order": {"total": 20, "lines": [3, 4]}, "order.total": 5});
+        let mut rule = expression("order.total * 2", Constraint::IntEquals(10.into())).unwrap();
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+
+        rule = expression("order.total * 2", Constraint::IntEquals(40.into())).unwrap();
+        res = rule.check_value_with_options(&map, &dot);
+        assert!(res.status == Status::Met);
+
+        rule = expression(
+            "order.lines[1] - /order/lines/0",
+            Constraint::IntEquals(1.into()),
+        )
+        .unwrap();
+        res = rule.check_value_with_options(&map, &dot);
+        assert!(res.status == Status::Met);
+
+        let rule = serde_json::from_value::<Rule>(json!({
+            "conditions": {"expr": "order.total + 1", "operator": "int_equals", "value": 21},
+            "event": {
+                "type": "message",
+                "params": {"type": "info", "title": "total", "message": "total"}
+            },
+            "path_dialect": "dot"
+        }))
+        .unwrap();
+        let res = rule.check_value(&map);
+        assert!(res.condition_result.status == Status::Met);
+    }
+
+    #[test]
+    fn check_status_rules() {
+        let map = json!({
+            "foo": 1,
+            "bar": "bar",
+            "items": [{"sku": "a"}, {"sku": "b"}]
+        });
+        let legacy = CheckOptions {
+            legacy_at_least: true,
+            ..CheckOptions::default()
+        };
+        let met = || int_equals("foo", 1);
+        let not_met = || int_equals("foo", 2);
+        // Missing fact
+        let unknown = || int_equals("quux", 1);
+
+        let rules = vec![
+            and(vec![met(), not_met(), unknown()]),
+            and(vec![met(), unknown()]),
+            or(vec![not_met(), met(), unknown()]),
+            or(vec![not_met(), unknown()]),
+            not(unknown()),
+            at_least(2, vec![met(), met(), not_met()]),
+            at_least(2, vec![met(), not_met(), unknown()]),
+            at_least(2, vec![not_met(), not_met(), met(), met()]),
+            at_least(3, vec![met(), not_met(), unknown(), met()]),
+            exactly(1, vec![met(), met(), unknown()]),
+            exactly(1, vec![met(), not_met(), not_met()]),
+            exactly(1, vec![met(), unknown(), not_met()]),
+            exactly(2, vec![not_met(), not_met(), met()]),
+            at_most(1, vec![met(), met(), unknown()]),
+            at_most(1, vec![not_met(), unknown(), unknown()]),
+            none(vec![not_met(), met(), unknown()]),
+            none(vec![not_met(), unknown()]),
+            any_element("items", string_equals("sku", "b")),
+            all_elements("items", string_equals("sku", "a")),
+            no_element("items", string_equals("sku", "c")),
+            all_elements("bar", string_equals("sku", "a")),
+            select_any("/items/*/sku", Constraint::StringEquals("b".into())).unwrap(),
+            select_all("/quux/*", Constraint::StringEquals("b".into())).unwrap(),
+        ];
+
+        for rule in &rules {
+            for options in &[CheckOptions::default(), legacy.clone()] {
+                assert_eq!(
+                    rule.check_status_with_options(&map, options),
+                    rule.check_value_with_options(&map, options).status
+                );
+            }
+        }
+
+        assert!(rules[0].check_status(&map) == Status::NotMet);
+        assert!(rules[1].check_status(&map) == Status::Unknown);
+        assert!(rules[2].check_status(&map) == Status::Met);
+        assert!(rules[8].check_status(&map) == Status::Unknown);
+        assert!(rules[8].check_status_with_options(&map, &legacy) == Status::NotMet);
+    }
+
+    #[test]
+    fn compiled_condition_rules() {
+        let map = json!({
+            "foo": 1,
+            "bar": "bar",
+            "order": {"total": 120.5, "items": [{"sku": "a", "qty": 2}, {"sku": "b", "qty": 0}]},
+            "customer": {"credit_limit": 100, "tags": ["vip"]},
+            "a/b": {"~c": true}
+        });
+        let dot = CheckOptions {
+            path_dialect: PathDialect::Dot,
+            ..CheckOptions::default()
+        };
+        let legacy = CheckOptions {
+            legacy_at_least: true,
+            ..CheckOptions::default()
+        };
+        let conditions = || {
+            vec![
+                json!({"and": [
+                    {"field": "foo", "operator": "int_equals", "value": 1},
+                    {"and": [
+                        {"field": "bar", "operator": "string_equals", "value": "bar"},
+                        {"field": "/foo", "operator": "int_greater_than", "value": 0}
+                    ]}
+                ]}),
+                json!({"or": [
+                    {"field": "foo", "operator": "int_equals", "value": 2},
+                    {"or": [{"field": "quux", "operator": "int_equals", "value": 2}]}
+                ]}),
+                json!({"not": {"field": "/a~1b/~0c", "operator": "bool_equals", "value": true}}),
+                json!({"should_minimum_meet": 2, "conditions": [
+                    {"field": "foo", "operator": "int_equals", "value": 1},
+                    {"field": "quux", "operator": "int_equals", "value": 1},
+                    {"field": "bar", "operator": "string_equals", "value": "baz"}
+                ]}),
+                json!({"exactly": 1, "conditions": [
+                    {"field": "foo", "operator": "int_equals", "value": 1},
+                    {"field": "bar", "operator": "string_equals", "value": "baz"}
+                ]}),
+                json!({"at_most": 0, "conditions": [
+                    {"field": "quux", "operator": "int_equals", "value": 1}
+                ]}),
+                json!({"none": [{"field": "foo", "operator": "int_equals", "value": 1}]}),
+                json!({
+                    "field": "/order/total",
+                    "operator": "float_greater_than",
+                    "value": {"fact": "/customer/credit_limit"}
+                }),
+                json!({"expr": "foo * 2", "operator": "float_equals", "value": 2.0}),
+                json!({"expr": "order.total / 2 + foo", "operator": "float_greater_than", "value": 60}),
+                json!({"expr": "foo / (foo - 1)", "operator": "float_greater_than", "value": 0}),
+                json!({"expr": "/order/total - foo", "operator": "float_equals", "value": 119.5}),
+                json!({"select": "/order/items/*/qty", "quantifier": "all", "operator": "int_greater_than", "value": 0}),
+                json!({"select": "$..sku", "quantifier": "any", "operator": "string_equals", "value": "b"}),
+                json!({"select": "/nothing/*", "quantifier": "any", "operator": "not_exists"}),
+                json!({"select": "/order/items/*/price", "quantifier": "all", "operator": "float_greater_than", "value": 0}),
+                json!({"field": "/order/items", "any": {"field": "qty", "operator": "int_equals", "value": 0}}),
+                json!({"field": "/order/items", "all": {"field": "sku", "operator": "string_equals", "value": "a"}}),
+                json!({"field": "/customer/tags", "none": {"field": "", "operator": "string_equals", "value": "vip"}}),
+                json!({"field": "bar", "any": {"field": "qty", "operator": "int_equals", "value": 0}}),
+                json!({"field": "order.items[0].sku", "operator": "string_equals", "value": "a"}),
+                json!({"field": "customer.tags", "operator": "contains_all", "value": ["vip"]}),
+            ]
+            .into_iter()
+            .map(|c| serde_json::from_value::<Condition>(c).unwrap())
+        };
+
+        for options in &[CheckOptions::default(), dot, legacy] {
+            for (condition, compiled) in conditions().zip(conditions()) {
+                let compiled = CompiledCondition::new(compiled, options.clone());
+                assert_eq!(
+                    compiled.check_status(&map),
+                    condition.check_value_with_options(&map, options).status,
+                    "{:?}",
+                    condition
+                );
+            }
+        }
     }
 
     #[test]
@@ -505,4 +3251,221 @@ mod tests {
         res = rule.check_value(&map);
         assert!(res.status == Status::NotMet);
     }
+
+    #[test]
+    fn coercion_rules() {
+        let map = json!({
+            "whole": 5.0,
+            "count": " 5 ",
+            "price": "9.5",
+            "flag": "tRuE",
+            "zip": 12345,
+            "sizes": ["1", "2"]
+        });
+        let lenient_numeric = CheckOptions {
+            coercion: Coercion::LenientNumeric,
+            ..CheckOptions::default()
+        };
+        let lenient_all = CheckOptions {
+            coercion: Coercion::LenientAll,
+            ..CheckOptions::default()
+        };
+
+        let mut rule = int_equals("whole", 5);
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+
+        res = rule.check_value_with_options(&map, &lenient_numeric);
+        assert!(res.status == Status::Met);
+
+        rule = int_equals("count", 5);
+        res = rule.check_value_with_options(&map, &lenient_numeric);
+        assert!(res.status == Status::Met);
+
+        rule = float_greater_than("price", 9.0);
+        res = rule.check_value_with_options(&map, &lenient_numeric);
+        assert!(res.status == Status::Met);
+
+        rule = int_contains("sizes", 2);
+        res = rule.check_value_with_options(&map, &lenient_numeric);
+        assert!(res.status == Status::Met);
+
+        // Bools and strings are only converted by lenient_all
+        rule = bool_equals("flag", true);
+        res = rule.check_value_with_options(&map, &lenient_numeric);
+        assert!(res.status == Status::NotMet);
+
+        res = rule.check_value_with_options(&map, &lenient_all);
+        assert!(res.status == Status::Met);
+
+        rule = string_equals("zip", "12345");
+        res = rule.check_value_with_options(&map, &lenient_all);
+        assert!(res.status == Status::Met);
+
+        // A condition can override the engine's policy
+        rule = with_modifiers(
+            int_equals("whole", 5),
+            Modifiers {
+                coercion: Some(Coercion::Strict),
+                ..Modifiers::default()
+            },
+        );
+        res = rule.check_value_with_options(&map, &lenient_all);
+        assert!(res.status == Status::NotMet);
+
+        rule = serde_json::from_value::<Condition>(json!({
+            "field": "flag",
+            "operator": "bool_equals",
+            "value": true,
+            "coercion": "lenient_all"
+        }))
+        .unwrap();
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+    }
+
+    #[test]
+    fn coercion_reference_rules() {
+        let map = json!({
+            "age": 30,
+            "limit": "100",
+            "price": "9.5",
+            "quantity": 2,
+            "flag": true,
+            "expected": "TRUE"
+        });
+        let rule = |v: Value| serde_json::from_value::<Condition>(v).unwrap();
+        let comparison = || {
+            rule(json!({
+                "field": "age",
+                "operator": "int_less_than",
+                "value": {"fact": "limit"}
+            }))
+        };
+        let expression = || {
+            rule(json!({
+                "expr": "price * quantity",
+                "operator": "float_equals",
+                "value": 19.0
+            }))
+        };
+        let flag = || {
+            rule(json!({
+                "field": "flag",
+                "operator": "bool_equals",
+                "value": {"fact": "expected"}
+            }))
+        };
+
+        for &(coercion, numeric, all) in &[
+            (Coercion::Strict, Status::NotMet, Status::NotMet),
+            (Coercion::LenientNumeric, Status::Met, Status::NotMet),
+            (Coercion::LenientAll, Status::Met, Status::Met),
+        ] {
+            let options = CheckOptions {
+                coercion,
+                ..CheckOptions::default()
+            };
+
+            // The referenced fact and expression operands follow the same policy as the field
+            let mut res = comparison().check_value_with_options(&map, &options);
+            assert!(res.status == numeric);
+            assert!(res.reason == (numeric == Status::NotMet).then_some(Reason::TypeMismatch));
+
+            res = expression().check_value_with_options(&map, &options);
+            assert!(res.status == numeric);
+
+            res = flag().check_value_with_options(&map, &options);
+            assert!(res.status == all);
+
+            let rules: [(&dyn Fn() -> Condition, Status); 3] =
+                [(&comparison, numeric), (&expression, numeric), (&flag, all)];
+            for &(rule, status) in &rules {
+                let compiled = CompiledCondition::new(rule(), options.clone());
+                assert!(compiled.check_status(&map) == status);
+            }
+        }
+    }
+
+    #[test]
+    fn type_mismatch_rules() {
+        let map = json!({
+            "name": 42,
+            "age": "old",
+            "items": {"sku": "a"}
+        });
+
+        let mut rule = string_not_equals("name", "John");
+        let mut res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+        assert!(res.reason == Some(Reason::TypeMismatch));
+
+        rule = string_not_equals("age", "young");
+        res = rule.check_value(&map);
+        assert!(res.status == Status::Met);
+        assert!(res.reason.is_none());
+
+        rule = int_equals("age", 5);
+        res = rule.check_value(&map);
+        assert!(res.reason == Some(Reason::TypeMismatch));
+
+        // A plain mismatch has no reason
+        rule = int_equals("name", 5);
+        res = rule.check_value(&map);
+        assert!(res.status == Status::NotMet);
+        assert!(res.reason.is_none());
+
+        rule = any_element("items", string_equals("sku", "a"));
+        res = rule.check_value(&map);
+        assert!(res.reason == Some(Reason::TypeMismatch));
+
+        let options = CheckOptions {
+            type_mismatch: TypeMismatch::Unknown,
+            ..CheckOptions::default()
+        };
+        rule = and(vec![int_equals("name", 42), string_equals("name", "42")]);
+        res = rule.check_value_with_options(&map, &options);
+        assert!(res.status == Status::Unknown);
+        assert!(res.find_reason(Reason::TypeMismatch).unwrap().name == "name");
+
+        let mut engine = Engine::with_options(CheckOptions {
+            type_mismatch: TypeMismatch::Error,
+            ..CheckOptions::default()
+        });
+        engine.add_rule(
+            serde_json::from_value::<Rule>(json!({
+                "conditions": {"field": "age", "operator": "int_greater_than", "value": 18},
+                "event": {
+                    "type": "message",
+                    "params": {"type": "info", "title": "adult", "message": "adult"}
+                }
+            }))
+            .unwrap(),
+        );
+        let err = engine.run(&map).now_or_never().unwrap().unwrap_err();
+        assert!(matches!(err, Error::TypeMismatchError(ref field) if field == "age"));
+
+        // Division by zero isn't a type mismatch
+        let mut engine = Engine::with_options(CheckOptions {
+            type_mismatch: TypeMismatch::Error,
+            ..CheckOptions::default()
+        });
+        engine.add_rule(
+            serde_json::from_value::<Rule>(json!({
+                "conditions": {"expr": "name / 0", "operator": "float_greater_than", "value": 1},
+                "event": {
+                    "type": "message",
+                    "params": {"type": "info", "title": "ratio", "message": "ratio"}
+                }
+            }))
+            .unwrap(),
+        );
+        let results = engine.run(&map).now_or_never().unwrap().unwrap();
+        assert!(results.is_empty());
+
+        rule = expression("name / 0", Constraint::FloatGreaterThan(1.0)).unwrap();
+        res = rule.check_value_with_options(&map, &options);
+        assert!(res.status == Status::Unknown);
+        assert!(res.reason == Some(Reason::DivisionByZero));
+    }
 }
@@ -0,0 +1,164 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+use unicode_normalization::UnicodeNormalization;
+
+/// Unicode normalization form applied before comparing strings
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Normalization {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+/// How facts of the wrong JSON type are converted before a `Constraint` checks them
+///
+/// Float operators always accept integers, and decimal operators numeric strings,
+/// whatever the policy.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Coercion {
+    /// Facts must already have the operator's type
+    #[default]
+    Strict,
+    /// Int operators accept whole floats, int and float operators accept numeric strings
+    LenientNumeric,
+    /// As `LenientNumeric`, bool operators also accept `"true"`/`"false"` in any case
+    /// and string operators accept numbers and bools
+    LenientAll,
+}
+
+impl Coercion {
+    /// Converts `v` for an int operator, `None` if it's left as is
+    pub fn for_int(self, v: &Value) -> Option<Value> {
+        if self == Coercion::Strict || v.is_i64() || v.is_u64() {
+            return None;
+        }
+
+        let f = match *v {
+            Value::Number(ref n) => n.as_f64()?,
+            Value::String(ref s) => {
+                let s = s.trim();
+                if let Ok(i) = s.parse::<i64>() {
+                    return Some(i.into());
+                } else if let Ok(u) = s.parse::<u64>() {
+                    return Some(u.into());
+                }
+                s.parse::<f64>().ok()?
+            }
+            _ => return None,
+        };
+
+        if f.fract() != 0.0 {
+            None
+        } else if f >= i64::MIN as f64 && f < i64::MAX as f64 {
+            Some((f as i64).into())
+        } else if f >= 0.0 && f < u64::MAX as f64 {
+            Some((f as u64).into())
+        } else {
+            None
+        }
+    }
+
+    /// Converts `v` for a float operator, `None` if it's left as is
+    pub fn for_float(self, v: &Value) -> Option<Value> {
+        if self == Coercion::Strict {
+            return None;
+        }
+
+        let f = v.as_str()?.trim().parse::<f64>().ok()?;
+        Number::from_f64(f).map(Value::Number)
+    }
+
+    /// Converts `v` for a bool operator, `None` if it's left as is
+    pub fn for_bool(self, v: &Value) -> Option<Value> {
+        if self != Coercion::LenientAll {
+            return None;
+        }
+
+        let s = v.as_str()?.trim();
+        if s.eq_ignore_ascii_case("true") {
+            Some(Value::Bool(true))
+        } else if s.eq_ignore_ascii_case("false") {
+            Some(Value::Bool(false))
+        } else {
+            None
+        }
+    }
+
+    /// Converts `v` for a string operator, `None` if it's left as is
+    pub fn for_string(self, v: &Value) -> Option<Value> {
+        if self != Coercion::LenientAll {
+            return None;
+        }
+
+        match *v {
+            Value::Number(ref n) => Some(Value::String(n.to_string())),
+            Value::Bool(b) => Some(Value::String(b.to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// Per-condition modifiers, given alongside `field`/`operator`/`value`
+///
+/// String modifiers are applied to both the fact and the rule's values before
+/// any string `Constraint` compares them. Patterns (`string_matches`, `glob`) are
+/// matched case-insensitively rather than lowercased.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Modifiers {
+    /// Ignore case when comparing strings
+    ///
+    /// Strings are compared by their Unicode lowercase mapping, not by full case
+    /// folding, so e.g. `"straße"` doesn't match `"STRASSE"`.
+    #[serde(skip_serializing_if = "is_false")]
+    pub case_insensitive: bool,
+    /// Normalize strings to the given unicode form
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalize: Option<Normalization>,
+    /// Strip leading and trailing whitespace
+    #[serde(skip_serializing_if = "is_false")]
+    pub trim: bool,
+    /// Overrides the engine's `Coercion` policy for this condition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coercion: Option<Coercion>,
+}
+
+impl Modifiers {
+    /// Applies `trim`, `normalize` and `case_insensitive`, in that order
+    pub fn apply<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        let mut s = Cow::Borrowed(if self.trim { s.trim() } else { s });
+
+        if let Some(form) = self.normalize {
+            s = Cow::Owned(match form {
+                Normalization::Nfc => s.nfc().collect(),
+                Normalization::Nfd => s.nfd().collect(),
+                Normalization::Nfkc => s.nfkc().collect(),
+                Normalization::Nfkd => s.nfkd().collect(),
+            });
+        }
+
+        if self.case_insensitive {
+            s = Cow::Owned(s.to_lowercase());
+        }
+
+        s
+    }
+
+    /// Applies `trim` and `normalize` only, for strings tested against a pattern
+    pub fn apply_for_pattern<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        Modifiers {
+            case_insensitive: false,
+            ..self.clone()
+        }
+        .apply(s)
+    }
+}
+
+fn is_false(b: &bool) -> bool {
+    !*b
+}
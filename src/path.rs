@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Syntax of the fields of a condition: its `field`, the `fact` of a field comparison
+/// and the facts of an expression
+///
+/// Selectors have their own syntax, see `Selector`.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathDialect {
+    /// RFC 6901 JSON pointer such as `/customer/address/city`, the leading `/` may be omitted
+    #[default]
+    Pointer,
+    /// Dot and bracket path such as `customer.address.city` or `items[0].sku`
+    ///
+    /// A `\` makes the next character part of the key, so `a\.b` is the single key
+    /// `a.b` and `a\[0]` the key `a[0]`. Fields starting with `/` are still read as
+    /// JSON pointers.
+    Dot,
+}
+
+impl PathDialect {
+    /// Turns a field written in this dialect into a JSON pointer
+    pub fn to_pointer(self, field: &str) -> String {
+        match self {
+            PathDialect::Dot if !field.starts_with('/') => dot_to_pointer(field),
+            _ => to_pointer(field),
+        }
+    }
+}
+
+/// JSON pointer split into its reference tokens ahead of time, so that looking it
+/// up neither parses nor allocates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Pointer {
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token {
+    key: String,
+    /// The key read as an array index, following the same rules as `Value::pointer`
+    index: Option<usize>,
+}
+
+impl Pointer {
+    /// Pointer to a field written in the given dialect
+    pub(crate) fn new(dialect: PathDialect, field: &str) -> Self {
+        let tokens = dialect
+            .to_pointer(field)
+            .split('/')
+            .skip(1)
+            .map(|key| {
+                let key = key.replace("~1", "/").replace("~0", "~");
+                let index = if key.starts_with('+') || (key.starts_with('0') && key.len() != 1) {
+                    None
+                } else {
+                    key.parse().ok()
+                };
+
+                Token { key, index }
+            })
+            .collect();
+
+        Pointer { tokens }
+    }
+
+    /// Same as `info.pointer(..)`
+    pub(crate) fn get<'a>(&self, info: &'a Value) -> Option<&'a Value> {
+        self.tokens.iter().try_fold(info, |v, token| match *v {
+            Value::Object(ref o) => o.get(&token.key),
+            Value::Array(ref a) => token.index.and_then(|i| a.get(i)),
+            _ => None,
+        })
+    }
+}
+
+/// Turns a field name into a JSON pointer, prefixing it with `/` if needed
+pub(crate) fn to_pointer(field: &str) -> String {
+    if field.starts_with('/') {
+        field.to_owned()
+    } else {
+        format!("/{}", field)
+    }
+}
+
+fn dot_to_pointer(field: &str) -> String {
+    let mut pointer = String::with_capacity(field.len() + 1);
+    let mut key = String::new();
+    // Whether the last segment was an index, so that the `.` in `[0].sku` doesn't
+    // start an empty key
+    let mut after_index = false;
+    let mut chars = field.char_indices();
+
+    let push = |pointer: &mut String, key: &str| {
+        pointer.push('/');
+        pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+    };
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    key.push(escaped);
+                }
+            }
+            '.' => {
+                if !(after_index && key.is_empty()) {
+                    push(&mut pointer, &key);
+                }
+                key.clear();
+                after_index = false;
+            }
+            '[' => {
+                let index = field[i + 1..]
+                    .find(']')
+                    .map(|end| &field[i + 1..i + 1 + end])
+                    .filter(|index| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()));
+
+                if let Some(index) = index {
+                    if !key.is_empty() {
+                        push(&mut pointer, &key);
+                    }
+                    push(&mut pointer, index);
+                    key.clear();
+                    after_index = true;
+                    // Skip the index and the closing bracket
+                    chars.nth(index.len());
+                } else {
+                    key.push(c);
+                }
+            }
+            c => key.push(c),
+        }
+    }
+
+    if !(after_index && key.is_empty()) {
+        push(&mut pointer, &key);
+    }
+
+    pointer
+}
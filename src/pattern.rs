@@ -0,0 +1,119 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use globset::{Glob, GlobBuilder, GlobMatcher};
+use regex::{Regex, RegexBuilder};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Regular expression compiled once when the rule is loaded
+///
+/// The case-insensitive variant is only compiled the first time it is needed.
+#[derive(Clone)]
+pub struct Pattern {
+    regex: Regex,
+    case_insensitive: OnceLock<Regex>,
+}
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Pattern {
+            regex: Regex::new(pattern)?,
+            case_insensitive: OnceLock::new(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    pub fn is_match(&self, s: &str) -> bool {
+        self.regex.is_match(s)
+    }
+
+    pub fn is_match_case_insensitive(&self, s: &str) -> bool {
+        self.case_insensitive
+            .get_or_init(|| {
+                RegexBuilder::new(self.as_str())
+                    .case_insensitive(true)
+                    .build()
+                    .unwrap_or_else(|_| self.regex.clone())
+            })
+            .is_match(s)
+    }
+}
+
+impl fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Pattern").field(&self.as_str()).finish()
+    }
+}
+
+impl Serialize for Pattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Pattern::new(&pattern).map_err(de::Error::custom)
+    }
+}
+
+/// Shell-style glob (`*`, `?`, `[a-z]`, `{a,b}`) compiled once when the rule is loaded
+///
+/// The case-insensitive variant is only compiled the first time it is needed.
+#[derive(Clone)]
+pub struct GlobPattern {
+    matcher: GlobMatcher,
+    case_insensitive: OnceLock<GlobMatcher>,
+}
+
+impl GlobPattern {
+    pub fn new(pattern: &str) -> Result<Self, globset::Error> {
+        Ok(GlobPattern {
+            matcher: Glob::new(pattern)?.compile_matcher(),
+            case_insensitive: OnceLock::new(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.matcher.glob().glob()
+    }
+
+    pub fn is_match(&self, s: &str) -> bool {
+        self.matcher.is_match(s)
+    }
+
+    pub fn is_match_case_insensitive(&self, s: &str) -> bool {
+        self.case_insensitive
+            .get_or_init(|| {
+                GlobBuilder::new(self.as_str())
+                    .case_insensitive(true)
+                    .build()
+                    .map(|glob| glob.compile_matcher())
+                    .unwrap_or_else(|_| self.matcher.clone())
+            })
+            .is_match(s)
+    }
+}
+
+impl fmt::Debug for GlobPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GlobPattern").field(&self.as_str()).finish()
+    }
+}
+
+impl Serialize for GlobPattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for GlobPattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        GlobPattern::new(&pattern).map_err(de::Error::custom)
+    }
+}
@@ -1,10 +1,25 @@
-use crate::error::Result;
+use crate::datetime::{parse_timestamp, DaysOfWeek, Span, TimeOfDayRange, Timestamp};
+use crate::decimal::{parse_decimal, DecimalNumber};
+use crate::error::{Error, Result};
+use crate::expr::{DivisionByZero, Expr};
+use crate::geo::{parse_point, GeoPolygon, GeoRadius};
+use crate::integer::Integer;
+use crate::ip::{canonical, is_private, parse_ip, CidrSet};
+use crate::modifiers::{Coercion, Modifiers};
+use crate::path::PathDialect;
+use crate::pattern::{GlobPattern, Pattern};
+use crate::selector::{Quantifier, Selector};
+use crate::similarity::SimilarTo;
 
+use std::borrow::Cow;
+use std::net::IpAddr;
 use std::ops::{BitAnd, BitOr, Not};
 
+use chrono::{DateTime, Utc};
 use futures_util::future::try_join_all;
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use semver::{Version, VersionReq};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use serde_json::{json, value::to_value, Value};
 
 // ***********************************************************************
@@ -55,6 +70,56 @@ impl Not for Status {
     }
 }
 
+// ***********************************************************************
+// OPTIONS
+// **********************************************************************
+/// Settings that change how a rules tree is evaluated
+#[derive(Debug, Clone, Default)]
+pub struct CheckOptions {
+    /// Evaluate `AtLeast` as `NotMet` whenever fewer than the required children are `Met`,
+    /// instead of `Unknown` when enough `Unknown` children could still reach the threshold
+    pub legacy_at_least: bool,
+    /// The current time used by date operators such as `within_last`,
+    /// defaults to the system clock when `None`
+    pub now: Option<DateTime<Utc>>,
+    /// How facts of the wrong JSON type are converted, conditions can override it
+    /// with their own `coercion` modifier
+    pub coercion: Coercion,
+    /// What a fact of the wrong type for its operator results in
+    pub type_mismatch: TypeMismatch,
+    /// Syntax of condition fields, a `Rule` can override it
+    pub path_dialect: PathDialect,
+}
+
+/// Outcome of a condition whose fact has the wrong type for its operator
+///
+/// The result always carries `Reason::TypeMismatch`, this only changes its status.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum TypeMismatch {
+    /// `NotMet`, like any other fact that doesn't match
+    #[default]
+    NotMet,
+    /// `Unknown`, as if the fact was missing
+    Unknown,
+    /// `NotMet`, and `Engine::run` fails with `Error::TypeMismatchError`
+    Error,
+}
+
+impl TypeMismatch {
+    pub(crate) fn status(self) -> Status {
+        match self {
+            TypeMismatch::Unknown => Status::Unknown,
+            TypeMismatch::NotMet | TypeMismatch::Error => Status::NotMet,
+        }
+    }
+}
+
+impl CheckOptions {
+    fn now(&self) -> DateTime<Utc> {
+        self.now.unwrap_or_else(Utc::now)
+    }
+}
+
 // ***********************************************************************
 // Rule
 // **********************************************************************
@@ -65,7 +130,7 @@ impl Not for Status {
 /// to construct the rules tree use the [convenience functions][1] in the module root.
 ///
 /// [1]: index.html#functions
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize)]
 #[serde(untagged)]
 pub enum Condition {
     And {
@@ -74,15 +139,217 @@ pub enum Condition {
     Or {
         or: Vec<Condition>,
     },
+    Not {
+        not: Box<Condition>,
+    },
+    AtLeast {
+        should_minimum_meet: usize,
+        conditions: Vec<Condition>,
+    },
+    Exactly {
+        exactly: usize,
+        conditions: Vec<Condition>,
+    },
+    AtMost {
+        at_most: usize,
+        conditions: Vec<Condition>,
+    },
+    None {
+        none: Vec<Condition>,
+    },
+    Condition {
+        field: String,
+        #[serde(flatten)]
+        constraint: Constraint,
+        #[serde(flatten)]
+        modifiers: Modifiers,
+    },
+    FieldComparison {
+        field: String,
+        #[serde(flatten)]
+        constraint: FactConstraint,
+        #[serde(flatten)]
+        modifiers: Modifiers,
+    },
+    Expression {
+        expr: Expr,
+        #[serde(flatten)]
+        constraint: Constraint,
+        #[serde(flatten)]
+        modifiers: Modifiers,
+    },
+    Selection {
+        select: Selector,
+        quantifier: Quantifier,
+        #[serde(flatten)]
+        constraint: Constraint,
+        #[serde(flatten)]
+        modifiers: Modifiers,
+    },
+    AnyElement {
+        field: String,
+        any: Box<Condition>,
+    },
+    AllElements {
+        field: String,
+        all: Box<Condition>,
+    },
+    NoElement {
+        field: String,
+        none: Box<Condition>,
+    },
+}
+
+/// Externally tagged mirror of `Condition`.
+///
+/// `Condition` picks the node kind from the keys present, then deserializes through
+/// this so that errors inside the node (e.g. an invalid regex) are reported as-is
+/// instead of as "data did not match any variant".
+#[derive(Deserialize)]
+#[serde(remote = "Condition")]
+enum ConditionDef {
+    And {
+        and: Vec<Condition>,
+    },
+    Or {
+        or: Vec<Condition>,
+    },
+    Not {
+        not: Box<Condition>,
+    },
     AtLeast {
         should_minimum_meet: usize,
         conditions: Vec<Condition>,
     },
+    Exactly {
+        exactly: usize,
+        conditions: Vec<Condition>,
+    },
+    AtMost {
+        at_most: usize,
+        conditions: Vec<Condition>,
+    },
+    None {
+        none: Vec<Condition>,
+    },
     Condition {
         field: String,
         #[serde(flatten)]
         constraint: Constraint,
+        #[serde(flatten)]
+        modifiers: Modifiers,
+    },
+    FieldComparison {
+        field: String,
+        #[serde(flatten)]
+        constraint: FactConstraint,
+        #[serde(flatten)]
+        modifiers: Modifiers,
+    },
+    Expression {
+        expr: Expr,
+        #[serde(flatten)]
+        constraint: Constraint,
+        #[serde(flatten)]
+        modifiers: Modifiers,
+    },
+    Selection {
+        select: Selector,
+        quantifier: Quantifier,
+        #[serde(flatten)]
+        constraint: Constraint,
+        #[serde(flatten)]
+        modifiers: Modifiers,
     },
+    AnyElement {
+        field: String,
+        any: Box<Condition>,
+    },
+    AllElements {
+        field: String,
+        all: Box<Condition>,
+    },
+    NoElement {
+        field: String,
+        none: Box<Condition>,
+    },
+}
+
+impl<'de> Deserialize<'de> for Condition {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        let kind = value
+            .as_object()
+            .and_then(|object| {
+                if object.contains_key("field") {
+                    if object.contains_key("any") {
+                        return Some("AnyElement");
+                    } else if object.contains_key("all") {
+                        return Some("AllElements");
+                    } else if object.contains_key("none") {
+                        return Some("NoElement");
+                    }
+
+                    let operator = object.get("operator").and_then(Value::as_str);
+                    match object.get("value") {
+                        Some(v)
+                            if FactReference::is_reference(v)
+                                && operator.is_some_and(FactConstraint::supports) =>
+                        {
+                            Some("FieldComparison")
+                        }
+                        _ => Some("Condition"),
+                    }
+                } else if object.contains_key("expr") {
+                    Some("Expression")
+                } else if object.contains_key("select") {
+                    Some("Selection")
+                } else if object.contains_key("and") {
+                    Some("And")
+                } else if object.contains_key("or") {
+                    Some("Or")
+                } else if object.contains_key("not") {
+                    Some("Not")
+                } else if object.contains_key("should_minimum_meet") {
+                    Some("AtLeast")
+                } else if object.contains_key("exactly") {
+                    Some("Exactly")
+                } else if object.contains_key("at_most") {
+                    Some("AtMost")
+                } else if object.contains_key("none") {
+                    Some("None")
+                } else {
+                    None
+                }
+            })
+            .ok_or_else(|| {
+                de::Error::custom(
+                    "expected a condition with one of `field`, `expr`, `select`, `and`, `or`, `not`, \
+                     `should_minimum_meet`, `exactly`, `at_most` or `none`",
+                )
+            })?;
+
+        // A reference given to an operator without a fact form is most likely meant as one,
+        // so say which operators have it rather than only why the literal didn't parse
+        let reference =
+            kind == "Condition" && value.get("value").is_some_and(FactReference::is_reference);
+
+        ConditionDef::deserialize(json!({ kind: value })).map_err(|e| {
+            if reference {
+                de::Error::custom(format!(
+                    "{}; comparing against another fact with `{{\"fact\": ..}}` is only \
+                     supported by `{}`",
+                    e,
+                    FactConstraint::OPERATORS.join("`, `"),
+                ))
+            } else {
+                de::Error::custom(e)
+            }
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,11 +376,25 @@ pub enum Event {
 pub struct Rule {
     conditions: Condition,
     event: Event,
+    /// Syntax of this rule's condition fields, overriding the engine's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path_dialect: Option<PathDialect>,
 }
 
 impl Rule {
     pub fn check_value(&self, info: &Value) -> RuleResult {
-        let condition_result = self.conditions.check_value(info);
+        self.check_value_with_options(info, &CheckOptions::default())
+    }
+
+    pub fn check_value_with_options(&self, info: &Value, options: &CheckOptions) -> RuleResult {
+        let options = match self.path_dialect {
+            Some(path_dialect) => Cow::Owned(CheckOptions {
+                path_dialect,
+                ..options.clone()
+            }),
+            None => Cow::Borrowed(options),
+        };
+        let condition_result = self.conditions.check_value_with_options(info, &options);
         let mut event = self.event.to_owned();
 
         match event {
@@ -131,19 +412,36 @@ impl Rule {
             event,
         }
     }
+
+    /// Reads this rule's condition fields with `path_dialect` whatever the engine's
+    pub fn set_path_dialect(&mut self, path_dialect: PathDialect) {
+        self.path_dialect = Some(path_dialect);
+    }
 }
 
 #[derive(Debug)]
 pub struct Engine {
     rules: Vec<Rule>,
     client: Client,
+    options: CheckOptions,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Engine {
     pub fn new() -> Self {
+        Self::with_options(CheckOptions::default())
+    }
+
+    pub fn with_options(options: CheckOptions) -> Self {
         Self {
             rules: Vec::new(),
             client: Client::new(),
+            options,
         }
     }
 
@@ -153,10 +451,23 @@ impl Engine {
 
     pub async fn run<T: Serialize>(&self, facts: &T) -> Result<Vec<RuleResult>> {
         let facts = to_value(facts)?;
-        let rule_results: Vec<RuleResult> = self
+        let rule_results = self
             .rules
             .iter()
-            .map(|rule| rule.check_value(&facts))
+            .map(|rule| rule.check_value_with_options(&facts, &self.options))
+            .collect::<Vec<_>>();
+
+        if self.options.type_mismatch == TypeMismatch::Error {
+            if let Some(result) = rule_results
+                .iter()
+                .find_map(|r| r.condition_result.find_reason(Reason::TypeMismatch))
+            {
+                return Err(Error::TypeMismatchError(result.name.clone()));
+            }
+        }
+
+        let rule_results: Vec<RuleResult> = rule_results
+            .into_iter()
             .filter(|rule_result| rule_result.condition_result.status == Status::Met)
             .collect();
 
@@ -188,12 +499,21 @@ impl Condition {
     /// Starting at this node, recursively check (depth-first) any child nodes and
     /// aggregate the results
     pub fn check_value(&self, info: &Value) -> ConditionResult {
+        self.check_value_with_options(info, &CheckOptions::default())
+    }
+
+    /// Same as `check_value`, evaluating the tree with the given `CheckOptions`
+    pub fn check_value_with_options(
+        &self,
+        info: &Value,
+        options: &CheckOptions,
+    ) -> ConditionResult {
         match *self {
             Condition::And { ref and } => {
                 let mut status = Status::Met;
                 let children = and
                     .iter()
-                    .map(|c| c.check_value(info))
+                    .map(|c| c.check_value_with_options(info, options))
                     .inspect(|r| status = status & r.status)
                     .collect::<Vec<_>>();
 
@@ -201,13 +521,14 @@ impl Condition {
                     name: "And".into(),
                     status,
                     children,
+                    reason: None,
                 }
             }
             Condition::Or { ref or } => {
                 let mut status = Status::NotMet;
                 let children = or
                     .iter()
-                    .map(|c| c.check_value(info))
+                    .map(|c| c.check_value_with_options(info, options))
                     .inspect(|r| status = status | r.status)
                     .collect::<Vec<_>>();
 
@@ -215,111 +536,1556 @@ impl Condition {
                     name: "Or".into(),
                     status,
                     children,
+                    reason: None,
+                }
+            }
+            Condition::Not { ref not } => {
+                let child = not.check_value_with_options(info, options);
+
+                ConditionResult {
+                    name: "Not".into(),
+                    status: !child.status,
+                    children: vec![child],
+                    reason: None,
                 }
             }
             Condition::AtLeast {
                 should_minimum_meet,
                 ref conditions,
             } => {
-                let mut met_count = 0;
-                let children = conditions
-                    .iter()
-                    .map(|c| c.check_value(info))
-                    .inspect(|r| {
-                        if r.status == Status::Met {
-                            met_count += 1;
+                let (children, met_count, unknown_count) =
+                    check_counting(conditions, info, options);
+                let status = Threshold::AtLeast(should_minimum_meet).status(
+                    met_count,
+                    unknown_count,
+                    options,
+                );
+
+                ConditionResult {
+                    name: format!(
+                        "At least meet {} of {}",
+                        should_minimum_meet,
+                        conditions.len()
+                    ),
+                    status,
+                    children,
+                    reason: None,
+                }
+            }
+            Condition::Exactly {
+                exactly,
+                ref conditions,
+            } => {
+                let (children, met_count, unknown_count) =
+                    check_counting(conditions, info, options);
+                let status = Threshold::Exactly(exactly).status(met_count, unknown_count, options);
+
+                ConditionResult {
+                    name: format!("Exactly meet {} of {}", exactly, conditions.len()),
+                    status,
+                    children,
+                    reason: None,
+                }
+            }
+            Condition::AtMost {
+                at_most,
+                ref conditions,
+            } => {
+                let (children, met_count, unknown_count) =
+                    check_counting(conditions, info, options);
+                let status = Threshold::AtMost(at_most).status(met_count, unknown_count, options);
+
+                ConditionResult {
+                    name: format!("At most meet {} of {}", at_most, conditions.len()),
+                    status,
+                    children,
+                    reason: None,
+                }
+            }
+            Condition::None { ref none } => {
+                let (children, met_count, unknown_count) = check_counting(none, info, options);
+                let status = Threshold::AtMost(0).status(met_count, unknown_count, options);
+
+                ConditionResult {
+                    name: format!("None of {}", none.len()),
+                    status,
+                    children,
+                    reason: None,
+                }
+            }
+            Condition::Condition {
+                ref field,
+                ref constraint,
+                ref modifiers,
+            } => {
+                let (status, reason) = check_field(field, constraint, modifiers, info, options);
+
+                ConditionResult {
+                    name: field.to_owned(),
+                    status,
+                    children: Vec::new(),
+                    reason,
+                }
+            }
+            Condition::FieldComparison {
+                ref field,
+                ref constraint,
+                ref modifiers,
+            } => {
+                let (status, reason) =
+                    check_field_comparison(field, constraint, modifiers, info, options);
+
+                ConditionResult {
+                    name: field.to_owned(),
+                    status,
+                    children: Vec::new(),
+                    reason,
+                }
+            }
+            Condition::Expression {
+                ref expr,
+                ref constraint,
+                ref modifiers,
+            } => {
+                let (status, reason) = check_expression(expr, constraint, modifiers, info, options);
+
+                ConditionResult {
+                    name: expr.to_string(),
+                    status,
+                    children: Vec::new(),
+                    reason,
+                }
+            }
+            Condition::Selection {
+                ref select,
+                quantifier,
+                ref constraint,
+                ref modifiers,
+            } => {
+                let (selected, resolved) = select.select_resolved(info);
+                let children = selected
+                    .into_iter()
+                    .map(|(path, v)| {
+                        let (status, reason) = match v {
+                            Some(v) => constraint.check_value_with_reason(v, modifiers, options),
+                            None => (constraint.check_missing(), None),
+                        };
+
+                        ConditionResult {
+                            name: path,
+                            status,
+                            children: Vec::new(),
+                            reason,
                         }
                     })
                     .collect::<Vec<_>>();
 
-                let status = if met_count >= should_minimum_meet {
+                let status = if children.is_empty() && !resolved {
+                    constraint.check_missing()
+                } else {
+                    match quantifier {
+                        Quantifier::Any => children
+                            .iter()
+                            .fold(Status::NotMet, |status, r| status | r.status),
+                        Quantifier::All => children
+                            .iter()
+                            .fold(Status::Met, |status, r| status & r.status),
+                    }
+                };
+
+                ConditionResult {
+                    name: match quantifier {
+                        Quantifier::Any => format!("Any of {}", select),
+                        Quantifier::All => format!("All of {}", select),
+                    },
+                    status,
+                    children,
+                    reason: None,
+                }
+            }
+            Condition::AnyElement { ref field, ref any } => {
+                let (status, children, reason) = match check_elements(field, any, info, options) {
+                    Ok(children) => (
+                        children
+                            .iter()
+                            .fold(Status::NotMet, |status, r| status | r.status),
+                        children,
+                        None,
+                    ),
+                    Err((status, reason)) => (status, Vec::new(), reason),
+                };
+
+                ConditionResult {
+                    name: format!("Any of {}", field),
+                    status,
+                    children,
+                    reason,
+                }
+            }
+            Condition::AllElements { ref field, ref all } => {
+                let (status, children, reason) = match check_elements(field, all, info, options) {
+                    Ok(children) => (
+                        children
+                            .iter()
+                            .fold(Status::Met, |status, r| status & r.status),
+                        children,
+                        None,
+                    ),
+                    Err((status, reason)) => (status, Vec::new(), reason),
+                };
+
+                ConditionResult {
+                    name: format!("All of {}", field),
+                    status,
+                    children,
+                    reason,
+                }
+            }
+            Condition::NoElement {
+                ref field,
+                ref none,
+            } => {
+                let (status, children, reason) = match check_elements(field, none, info, options) {
+                    Ok(children) => (
+                        !children
+                            .iter()
+                            .fold(Status::NotMet, |status, r| status | r.status),
+                        children,
+                        None,
+                    ),
+                    Err((status, reason)) => (status, Vec::new(), reason),
+                };
+
+                ConditionResult {
+                    name: format!("None of {}", field),
+                    status,
+                    children,
+                    reason,
+                }
+            }
+        }
+    }
+}
+
+impl Condition {
+    /// Same as `check_value` but only computes the top-level `Status`, stopping as
+    /// soon as the outcome is decided and without building the results tree
+    pub fn check_status(&self, info: &Value) -> Status {
+        self.check_status_with_options(info, &CheckOptions::default())
+    }
+
+    /// Same as `check_status`, evaluating the tree with the given `CheckOptions`
+    pub fn check_status_with_options(&self, info: &Value, options: &CheckOptions) -> Status {
+        let counting = |threshold: Threshold, conditions: &[Condition]| {
+            threshold.check(
+                conditions
+                    .iter()
+                    .map(|c| c.check_status_with_options(info, options)),
+                options,
+            )
+        };
+
+        match *self {
+            Condition::And { ref and } => all_status(
+                and.iter()
+                    .map(|c| c.check_status_with_options(info, options)),
+            ),
+            Condition::Or { ref or } => any_status(
+                or.iter()
+                    .map(|c| c.check_status_with_options(info, options)),
+            ),
+            Condition::Not { ref not } => !not.check_status_with_options(info, options),
+            Condition::AtLeast {
+                should_minimum_meet,
+                ref conditions,
+            } => counting(Threshold::AtLeast(should_minimum_meet), conditions),
+            Condition::Exactly {
+                exactly,
+                ref conditions,
+            } => counting(Threshold::Exactly(exactly), conditions),
+            Condition::AtMost {
+                at_most,
+                ref conditions,
+            } => counting(Threshold::AtMost(at_most), conditions),
+            Condition::None { ref none } => counting(Threshold::AtMost(0), none),
+            Condition::Condition {
+                ref field,
+                ref constraint,
+                ref modifiers,
+            } => check_field(field, constraint, modifiers, info, options).0,
+            Condition::FieldComparison {
+                ref field,
+                ref constraint,
+                ref modifiers,
+            } => check_field_comparison(field, constraint, modifiers, info, options).0,
+            Condition::Expression {
+                ref expr,
+                ref constraint,
+                ref modifiers,
+            } => check_expression(expr, constraint, modifiers, info, options).0,
+            Condition::Selection {
+                ref select,
+                quantifier,
+                ref constraint,
+                ref modifiers,
+            } => check_selection(select, quantifier, constraint, modifiers, info, options),
+            Condition::AnyElement { ref field, ref any } => match elements(field, info, options) {
+                Ok(elements) => any_status(
+                    elements
+                        .iter()
+                        .map(|e| any.check_status_with_options(e, options)),
+                ),
+                Err((status, _)) => status,
+            },
+            Condition::AllElements { ref field, ref all } => match elements(field, info, options) {
+                Ok(elements) => all_status(
+                    elements
+                        .iter()
+                        .map(|e| all.check_status_with_options(e, options)),
+                ),
+                Err((status, _)) => status,
+            },
+            Condition::NoElement {
+                ref field,
+                ref none,
+            } => match elements(field, info, options) {
+                Ok(elements) => !any_status(
+                    elements
+                        .iter()
+                        .map(|e| none.check_status_with_options(e, options)),
+                ),
+                Err((status, _)) => status,
+            },
+        }
+    }
+}
+
+/// How many children of a counting node must be `Met`, `None` is `AtMost(0)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Threshold {
+    AtLeast(usize),
+    Exactly(usize),
+    AtMost(usize),
+}
+
+impl Threshold {
+    /// Status given how many of the children are `Met` and `Unknown`
+    pub(crate) fn status(
+        self,
+        met_count: usize,
+        unknown_count: usize,
+        options: &CheckOptions,
+    ) -> Status {
+        match self {
+            Threshold::AtLeast(n) => {
+                if met_count >= n {
+                    Status::Met
+                } else if options.legacy_at_least || met_count + unknown_count < n {
+                    Status::NotMet
+                } else {
+                    Status::Unknown
+                }
+            }
+            Threshold::Exactly(n) => {
+                if met_count > n || met_count + unknown_count < n {
+                    Status::NotMet
+                } else if unknown_count == 0 {
+                    Status::Met
+                } else {
+                    Status::Unknown
+                }
+            }
+            Threshold::AtMost(n) => {
+                if met_count > n {
+                    Status::NotMet
+                } else if met_count + unknown_count <= n {
+                    Status::Met
+                } else {
+                    Status::Unknown
+                }
+            }
+        }
+    }
+
+    /// Status of the children's `statuses`, stopping as soon as it's decided
+    pub(crate) fn check(
+        self,
+        statuses: impl ExactSizeIterator<Item = Status>,
+        options: &CheckOptions,
+    ) -> Status {
+        let (mut met_count, mut unknown_count) = (0, 0);
+        let mut remaining = statuses.len();
+
+        for s in statuses {
+            remaining -= 1;
+            match s {
+                Status::Met => met_count += 1,
+                Status::Unknown => unknown_count += 1,
+                Status::NotMet => {}
+            }
+
+            // The status is monotonic in both counts, so it's decided once the
+            // remaining children being all `Met`, all `NotMet` or all `Unknown`
+            // would give the same one
+            let status = self.status(met_count, unknown_count, options);
+            if status == self.status(met_count + remaining, unknown_count, options)
+                && status == self.status(met_count, unknown_count + remaining, options)
+            {
+                return status;
+            }
+        }
+
+        self.status(met_count, unknown_count, options)
+    }
+}
+
+/// `Met` if any status is `Met`, stopping at the first one
+pub(crate) fn any_status(statuses: impl Iterator<Item = Status>) -> Status {
+    let mut status = Status::NotMet;
+    for s in statuses {
+        status = status | s;
+        if status == Status::Met {
+            break;
+        }
+    }
+    status
+}
+
+/// `Met` if every status is `Met`, stopping at the first `NotMet`
+pub(crate) fn all_status(statuses: impl Iterator<Item = Status>) -> Status {
+    let mut status = Status::Met;
+    for s in statuses {
+        status = status & s;
+        if status == Status::NotMet {
+            break;
+        }
+    }
+    status
+}
+
+fn check_field(
+    field: &str,
+    constraint: &Constraint,
+    modifiers: &Modifiers,
+    info: &Value,
+    options: &CheckOptions,
+) -> (Status, Option<Reason>) {
+    if let Some(s) = info.pointer(&options.path_dialect.to_pointer(field)) {
+        constraint.check_value_with_reason(s, modifiers, options)
+    } else {
+        (constraint.check_missing(), None)
+    }
+}
+
+fn check_field_comparison(
+    field: &str,
+    constraint: &FactConstraint,
+    modifiers: &Modifiers,
+    info: &Value,
+    options: &CheckOptions,
+) -> (Status, Option<Reason>) {
+    match (
+        info.pointer(&options.path_dialect.to_pointer(field)),
+        info.pointer(&options.path_dialect.to_pointer(&constraint.fact().fact)),
+    ) {
+        (Some(s), Some(other)) => {
+            match constraint.resolve(other, modifiers.coercion.unwrap_or(options.coercion)) {
+                Some(c) => c.check_value_with_reason(s, modifiers, options),
+                None => (options.type_mismatch.status(), Some(Reason::TypeMismatch)),
+            }
+        }
+        _ => (Status::Unknown, None),
+    }
+}
+
+/// Status of a selection, stopping as soon as it's decided
+pub(crate) fn check_selection(
+    select: &Selector,
+    quantifier: Quantifier,
+    constraint: &Constraint,
+    modifiers: &Modifiers,
+    info: &Value,
+    options: &CheckOptions,
+) -> Status {
+    let (mut status, decided) = match quantifier {
+        Quantifier::Any => (Status::NotMet, Status::Met),
+        Quantifier::All => (Status::Met, Status::NotMet),
+    };
+    let mut selected = false;
+
+    let resolved = select.visit(info, &mut |v| {
+        let s = match v {
+            Some(v) => constraint.check_value_with_options(v, modifiers, options),
+            None => constraint.check_missing(),
+        };
+        status = match quantifier {
+            Quantifier::Any => status | s,
+            Quantifier::All => status & s,
+        };
+        selected = true;
+        status != decided
+    });
+
+    if selected || resolved {
+        status
+    } else {
+        constraint.check_missing()
+    }
+}
+
+fn check_expression(
+    expr: &Expr,
+    constraint: &Constraint,
+    modifiers: &Modifiers,
+    info: &Value,
+    options: &CheckOptions,
+) -> (Status, Option<Reason>) {
+    let fact = |_, field: &str| info.pointer(&options.path_dialect.to_pointer(field));
+    check_expression_with(expr, constraint, modifiers, &fact, options)
+}
+
+/// Same as `check_expression` with `fact` looking up the expression's fields
+pub(crate) fn check_expression_with<'a>(
+    expr: &Expr,
+    constraint: &Constraint,
+    modifiers: &Modifiers,
+    fact: &dyn Fn(usize, &str) -> Option<&'a Value>,
+    options: &CheckOptions,
+) -> (Status, Option<Reason>) {
+    match expr.try_evaluate(fact, modifiers.coercion.unwrap_or(options.coercion)) {
+        Some(Ok(s)) => constraint.check_value_with_reason(&s, modifiers, options),
+        Some(Err(DivisionByZero)) => (Status::Unknown, Some(Reason::DivisionByZero)),
+        None => (Status::Unknown, None),
+    }
+}
+
+impl Condition {
+    /// Replaces the `Modifiers` of every leaf in this subtree
+    pub fn set_modifiers(&mut self, new: &Modifiers) {
+        match *self {
+            Condition::And { and: ref mut c }
+            | Condition::Or { or: ref mut c }
+            | Condition::None { none: ref mut c }
+            | Condition::AtLeast {
+                conditions: ref mut c,
+                ..
+            }
+            | Condition::Exactly {
+                conditions: ref mut c,
+                ..
+            }
+            | Condition::AtMost {
+                conditions: ref mut c,
+                ..
+            } => c.iter_mut().for_each(|c| c.set_modifiers(new)),
+            Condition::Not { ref mut not }
+            | Condition::AnyElement {
+                any: ref mut not, ..
+            }
+            | Condition::AllElements {
+                all: ref mut not, ..
+            }
+            | Condition::NoElement {
+                none: ref mut not, ..
+            } => not.set_modifiers(new),
+            Condition::Condition {
+                ref mut modifiers, ..
+            }
+            | Condition::FieldComparison {
+                ref mut modifiers, ..
+            }
+            | Condition::Expression {
+                ref mut modifiers, ..
+            }
+            | Condition::Selection {
+                ref mut modifiers, ..
+            } => *modifiers = new.clone(),
+        }
+    }
+}
+
+/// Checks `condition` against each element of the array at `field`, with the
+/// element as the root of the facts
+fn check_elements(
+    field: &str,
+    condition: &Condition,
+    info: &Value,
+    options: &CheckOptions,
+) -> std::result::Result<Vec<ConditionResult>, (Status, Option<Reason>)> {
+    Ok(elements(field, info, options)?
+        .iter()
+        .map(|element| condition.check_value_with_options(element, options))
+        .collect())
+}
+
+/// Elements of the array at `field`
+///
+/// Fails with the node's status if `field` is missing (`Unknown`) or isn't an array
+/// (a type mismatch).
+fn elements<'a>(
+    field: &str,
+    info: &'a Value,
+    options: &CheckOptions,
+) -> std::result::Result<&'a [Value], (Status, Option<Reason>)> {
+    match info.pointer(&options.path_dialect.to_pointer(field)) {
+        Some(Value::Array(elements)) => Ok(elements),
+        Some(_) => Err((options.type_mismatch.status(), Some(Reason::TypeMismatch))),
+        None => Err((Status::Unknown, None)),
+    }
+}
+
+/// Checks every child of a counting node and returns the results along with
+/// the number of `Met` and `Unknown` children
+fn check_counting(
+    conditions: &[Condition],
+    info: &Value,
+    options: &CheckOptions,
+) -> (Vec<ConditionResult>, usize, usize) {
+    let mut met_count = 0;
+    let mut unknown_count = 0;
+    let children = conditions
+        .iter()
+        .map(|c| c.check_value_with_options(info, options))
+        .inspect(|r| match r.status {
+            Status::Met => met_count += 1,
+            Status::Unknown => unknown_count += 1,
+            Status::NotMet => {}
+        })
+        .collect::<Vec<_>>();
+
+    (children, met_count, unknown_count)
+}
+
+// ***********************************************************************
+// CONSTRAINT
+// **********************************************************************
+/// Check applied to a fact, tagged by its snake_case `operator` name
+///
+/// Operators used to be deserialized from their PascalCase names (`"IntInRange"`); the
+/// ones that existed then still accept them as aliases, but they always serialize as
+/// snake_case.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[serde(tag = "operator", content = "value")]
+pub enum Constraint {
+    #[serde(alias = "StringEquals")]
+    StringEquals(String),
+    #[serde(alias = "StringNotEquals")]
+    StringNotEquals(String),
+    #[serde(alias = "StringContains")]
+    StringContains(String),
+    #[serde(alias = "StringDoesNotContain")]
+    StringDoesNotContain(String),
+    #[serde(alias = "StringIn")]
+    StringIn(Vec<String>),
+    #[serde(alias = "StringNotIn")]
+    StringNotIn(Vec<String>),
+    StringMatches(Pattern),
+    StringStartsWith(String),
+    StringEndsWith(String),
+    StringIncludesSubstring(String),
+    Glob(GlobPattern),
+    StringSimilar(SimilarTo),
+    #[serde(alias = "IntEquals")]
+    IntEquals(Integer),
+    #[serde(alias = "IntNotEquals")]
+    IntNotEquals(Integer),
+    #[serde(alias = "IntContains")]
+    IntContains(Integer),
+    #[serde(alias = "IntDoesNotContain")]
+    IntDoesNotContain(Integer),
+    #[serde(alias = "IntIn")]
+    IntIn(Vec<Integer>),
+    #[serde(alias = "IntNotIn")]
+    IntNotIn(Vec<Integer>),
+    #[serde(alias = "IntInRange")]
+    IntInRange(Integer, Integer),
+    #[serde(alias = "IntNotInRange")]
+    IntNotInRange(Integer, Integer),
+    #[serde(alias = "IntLessThan")]
+    IntLessThan(Integer),
+    #[serde(alias = "IntLessThanInclusive")]
+    IntLessThanInclusive(Integer),
+    #[serde(alias = "IntGreaterThan")]
+    IntGreaterThan(Integer),
+    #[serde(alias = "IntGreaterThanInclusive")]
+    IntGreaterThanInclusive(Integer),
+    #[serde(alias = "FloatEquals")]
+    FloatEquals(f64),
+    #[serde(alias = "FloatNotEquals")]
+    FloatNotEquals(f64),
+    #[serde(alias = "FloatContains")]
+    FloatContains(f64),
+    #[serde(alias = "FloatDoesNotContain")]
+    FloatDoesNotContain(f64),
+    #[serde(alias = "FloatIn")]
+    FloatIn(Vec<f64>),
+    #[serde(alias = "FloatNotIn")]
+    FloatNotIn(Vec<f64>),
+    #[serde(alias = "FloatInRange")]
+    FloatInRange(f64, f64),
+    #[serde(alias = "FloatNotInRange")]
+    FloatNotInRange(f64, f64),
+    #[serde(alias = "FloatLessThan")]
+    FloatLessThan(f64),
+    #[serde(alias = "FloatLessThanInclusive")]
+    FloatLessThanInclusive(f64),
+    #[serde(alias = "FloatGreaterThan")]
+    FloatGreaterThan(f64),
+    #[serde(alias = "FloatGreaterThanInclusive")]
+    FloatGreaterThanInclusive(f64),
+    #[serde(alias = "BoolEquals")]
+    BoolEquals(bool),
+    DateBefore(Timestamp),
+    DateAfter(Timestamp),
+    DateBetween(Timestamp, Timestamp),
+    WithinLast(Span),
+    DayOfWeekIn(DaysOfWeek),
+    TimeOfDayBetween(TimeOfDayRange),
+    Exists,
+    NotExists,
+    IsNull,
+    IsNotNull,
+    IsType(JsonType),
+    LengthEquals(usize),
+    LengthGreaterThan(usize),
+    LengthLessThan(usize),
+    LengthInRange(usize, usize),
+    DecimalEquals(DecimalNumber),
+    DecimalNotEquals(DecimalNumber),
+    DecimalIn(Vec<DecimalNumber>),
+    DecimalNotIn(Vec<DecimalNumber>),
+    DecimalInRange(DecimalNumber, DecimalNumber),
+    DecimalNotInRange(DecimalNumber, DecimalNumber),
+    DecimalLessThan(DecimalNumber),
+    DecimalLessThanInclusive(DecimalNumber),
+    DecimalGreaterThan(DecimalNumber),
+    DecimalGreaterThanInclusive(DecimalNumber),
+    SemverEquals(Version),
+    SemverGreaterThan(Version),
+    SemverLessThan(Version),
+    SemverInRange(Version, Version),
+    SemverSatisfies(VersionReq),
+    IpInCidr(CidrSet),
+    IpNotInCidr(CidrSet),
+    IpEquals(IpAddr),
+    IpIsPrivate,
+    GeoWithinRadius(GeoRadius),
+    GeoInPolygon(GeoPolygon),
+    ContainsAll(Vec<Value>),
+    ContainsAny(Vec<Value>),
+    ContainsNone(Vec<Value>),
+    SubsetOf(Vec<Value>),
+    SupersetOf(Vec<Value>),
+    Intersects(Vec<Value>),
+    ArrayEquals(Vec<Value>),
+    ArrayEqualsUnordered(Vec<Value>),
+    Equals(Value),
+    NotEquals(Value),
+    In(Vec<Value>),
+    MatchesSubset(Value),
+}
+
+/// JSON type tested by `Constraint::IsType`
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonType {
+    Null,
+    Bool,
+    Number,
+    /// A number written as an integer, as the int operators accept it, so `5.0` isn't one
+    Integer,
+    String,
+    Array,
+    Object,
+}
+
+impl JsonType {
+    pub fn matches(self, v: &Value) -> bool {
+        match self {
+            JsonType::Null => v.is_null(),
+            JsonType::Bool => v.is_boolean(),
+            JsonType::Number => v.is_number(),
+            JsonType::Integer => v.is_i64() || v.is_u64(),
+            JsonType::String => v.is_string(),
+            JsonType::Array => v.is_array(),
+            JsonType::Object => v.is_object(),
+        }
+    }
+}
+
+impl Constraint {
+    /// Status of this constraint when the field is absent from the facts
+    ///
+    /// Presence and type checks can be decided without a value, everything else is `Unknown`.
+    pub fn check_missing(&self) -> Status {
+        match *self {
+            Constraint::NotExists => Status::Met,
+            Constraint::Exists
+            | Constraint::IsNull
+            | Constraint::IsNotNull
+            | Constraint::IsType(_) => Status::NotMet,
+            _ => Status::Unknown,
+        }
+    }
+
+    pub fn check_value(&self, v: &Value) -> Status {
+        self.check_value_with_options(v, &Modifiers::default(), &CheckOptions::default())
+    }
+
+    /// Same as `check_value_with_options`, also giving the `Reason` for a `NotMet`
+    /// that isn't a plain mismatch
+    pub fn check_value_with_reason(
+        &self,
+        v: &Value,
+        modifiers: &Modifiers,
+        options: &CheckOptions,
+    ) -> (Status, Option<Reason>) {
+        let v = &*self.coerce(v, modifiers.coercion.unwrap_or(options.coercion));
+        let status = self.check_coerced(v, modifiers, options);
+
+        match status {
+            Status::NotMet => match self.reason(v, modifiers) {
+                Some(Reason::TypeMismatch) => {
+                    (options.type_mismatch.status(), Some(Reason::TypeMismatch))
+                }
+                reason => (status, reason),
+            },
+            _ => (status, None),
+        }
+    }
+
+    /// Converts `v` to the type this operator expects, following the `Coercion` policy
+    fn coerce<'a>(&self, v: &'a Value, coercion: Coercion) -> Cow<'a, Value> {
+        let convert = match *self {
+            Constraint::StringEquals(_)
+            | Constraint::StringNotEquals(_)
+            | Constraint::StringContains(_)
+            | Constraint::StringDoesNotContain(_)
+            | Constraint::StringIn(_)
+            | Constraint::StringNotIn(_)
+            | Constraint::StringMatches(_)
+            | Constraint::StringStartsWith(_)
+            | Constraint::StringEndsWith(_)
+            | Constraint::StringIncludesSubstring(_)
+            | Constraint::Glob(_)
+            | Constraint::StringSimilar(_) => Coercion::for_string,
+            Constraint::IntEquals(_)
+            | Constraint::IntNotEquals(_)
+            | Constraint::IntContains(_)
+            | Constraint::IntDoesNotContain(_)
+            | Constraint::IntIn(_)
+            | Constraint::IntNotIn(_)
+            | Constraint::IntInRange(_, _)
+            | Constraint::IntNotInRange(_, _)
+            | Constraint::IntLessThan(_)
+            | Constraint::IntLessThanInclusive(_)
+            | Constraint::IntGreaterThan(_)
+            | Constraint::IntGreaterThanInclusive(_) => Coercion::for_int,
+            Constraint::FloatEquals(_)
+            | Constraint::FloatNotEquals(_)
+            | Constraint::FloatContains(_)
+            | Constraint::FloatDoesNotContain(_)
+            | Constraint::FloatIn(_)
+            | Constraint::FloatNotIn(_)
+            | Constraint::FloatInRange(_, _)
+            | Constraint::FloatNotInRange(_, _)
+            | Constraint::FloatLessThan(_)
+            | Constraint::FloatLessThanInclusive(_)
+            | Constraint::FloatGreaterThan(_)
+            | Constraint::FloatGreaterThanInclusive(_) => Coercion::for_float,
+            Constraint::BoolEquals(_) => Coercion::for_bool,
+            _ => return Cow::Borrowed(v),
+        };
+
+        if coercion == Coercion::Strict {
+            return Cow::Borrowed(v);
+        }
+
+        match *self {
+            Constraint::StringContains(_)
+            | Constraint::StringDoesNotContain(_)
+            | Constraint::IntContains(_)
+            | Constraint::IntDoesNotContain(_)
+            | Constraint::FloatContains(_)
+            | Constraint::FloatDoesNotContain(_) => match *v {
+                Value::Array(ref xs) => Cow::Owned(Value::Array(
+                    xs.iter()
+                        .map(|x| convert(coercion, x).unwrap_or_else(|| x.clone()))
+                        .collect(),
+                )),
+                _ => Cow::Borrowed(v),
+            },
+            _ => convert(coercion, v).map_or(Cow::Borrowed(v), Cow::Owned),
+        }
+    }
+
+    fn reason(&self, v: &Value, modifiers: &Modifiers) -> Option<Reason> {
+        let matches_type = match *self {
+            Constraint::StringEquals(_)
+            | Constraint::StringNotEquals(_)
+            | Constraint::StringIn(_)
+            | Constraint::StringNotIn(_)
+            | Constraint::StringMatches(_)
+            | Constraint::StringStartsWith(_)
+            | Constraint::StringEndsWith(_)
+            | Constraint::StringIncludesSubstring(_)
+            | Constraint::Glob(_)
+            | Constraint::StringSimilar(_) => v.is_string(),
+            Constraint::StringContains(_)
+            | Constraint::StringDoesNotContain(_)
+            | Constraint::FloatContains(_)
+            | Constraint::FloatDoesNotContain(_) => v.is_array(),
+            Constraint::IntContains(_) | Constraint::IntDoesNotContain(_) => {
+                return match v.as_array() {
+                    Some(xs) => xs
+                        .iter()
+                        .find_map(|x| Integer::from_value(x).err().flatten()),
+                    None => Some(Reason::TypeMismatch),
+                };
+            }
+            Constraint::IntEquals(_)
+            | Constraint::IntNotEquals(_)
+            | Constraint::IntIn(_)
+            | Constraint::IntNotIn(_)
+            | Constraint::IntInRange(_, _)
+            | Constraint::IntNotInRange(_, _)
+            | Constraint::IntLessThan(_)
+            | Constraint::IntLessThanInclusive(_)
+            | Constraint::IntGreaterThan(_)
+            | Constraint::IntGreaterThanInclusive(_) => {
+                return match Integer::from_value(v) {
+                    Ok(_) => None,
+                    Err(reason) => reason.or(Some(Reason::TypeMismatch)),
+                };
+            }
+            Constraint::FloatEquals(_)
+            | Constraint::FloatNotEquals(_)
+            | Constraint::FloatIn(_)
+            | Constraint::FloatNotIn(_)
+            | Constraint::FloatInRange(_, _)
+            | Constraint::FloatNotInRange(_, _)
+            | Constraint::FloatLessThan(_)
+            | Constraint::FloatLessThanInclusive(_)
+            | Constraint::FloatGreaterThan(_)
+            | Constraint::FloatGreaterThanInclusive(_) => v.is_number(),
+            Constraint::BoolEquals(_) => v.is_boolean(),
+            Constraint::DateBefore(_)
+            | Constraint::DateAfter(_)
+            | Constraint::DateBetween(_, _)
+            | Constraint::WithinLast(_)
+            | Constraint::DayOfWeekIn(_)
+            | Constraint::TimeOfDayBetween(_) => parse_timestamp(v).is_some(),
+            Constraint::LengthEquals(_)
+            | Constraint::LengthGreaterThan(_)
+            | Constraint::LengthLessThan(_)
+            | Constraint::LengthInRange(_, _) => length(v, modifiers).is_some(),
+            Constraint::DecimalEquals(_)
+            | Constraint::DecimalNotEquals(_)
+            | Constraint::DecimalIn(_)
+            | Constraint::DecimalNotIn(_)
+            | Constraint::DecimalInRange(_, _)
+            | Constraint::DecimalNotInRange(_, _)
+            | Constraint::DecimalLessThan(_)
+            | Constraint::DecimalLessThanInclusive(_)
+            | Constraint::DecimalGreaterThan(_)
+            | Constraint::DecimalGreaterThanInclusive(_) => parse_decimal(v).is_some(),
+            Constraint::SemverEquals(_)
+            | Constraint::SemverGreaterThan(_)
+            | Constraint::SemverLessThan(_)
+            | Constraint::SemverInRange(_, _)
+            | Constraint::SemverSatisfies(_) => parse_version(v).is_some(),
+            Constraint::IpInCidr(_)
+            | Constraint::IpNotInCidr(_)
+            | Constraint::IpEquals(_)
+            | Constraint::IpIsPrivate => parse_ip(v).is_some(),
+            Constraint::GeoWithinRadius(_) | Constraint::GeoInPolygon(_) => {
+                parse_point(v).is_some()
+            }
+            Constraint::ContainsAll(_)
+            | Constraint::ContainsAny(_)
+            | Constraint::ContainsNone(_)
+            | Constraint::SubsetOf(_)
+            | Constraint::SupersetOf(_)
+            | Constraint::Intersects(_)
+            | Constraint::ArrayEquals(_)
+            | Constraint::ArrayEqualsUnordered(_) => v.is_array(),
+            Constraint::Equals(_)
+            | Constraint::NotEquals(_)
+            | Constraint::In(_)
+            | Constraint::MatchesSubset(_) => true,
+            Constraint::Exists
+            | Constraint::NotExists
+            | Constraint::IsNull
+            | Constraint::IsNotNull
+            | Constraint::IsType(_) => true,
+        };
+
+        if matches_type {
+            None
+        } else {
+            Some(Reason::TypeMismatch)
+        }
+    }
+
+    /// Same as `check_value`, applying the condition's `Modifiers` and the engine's
+    /// `CheckOptions`
+    pub fn check_value_with_options(
+        &self,
+        v: &Value,
+        modifiers: &Modifiers,
+        options: &CheckOptions,
+    ) -> Status {
+        self.check_value_with_reason(v, modifiers, options).0
+    }
+
+    fn check_coerced(&self, v: &Value, modifiers: &Modifiers, options: &CheckOptions) -> Status {
+        match *self {
+            Constraint::StringEquals(ref s) => {
+                if let Some(v) = v.as_str() {
+                    if modifiers.apply(v) == modifiers.apply(s) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::StringNotEquals(ref s) => {
+                if let Some(v) = v.as_str() {
+                    if modifiers.apply(v) != modifiers.apply(s) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::StringContains(ref s) => {
+                if let Some(v) = v.as_array().map(|x| {
+                    x.iter()
+                        .filter_map(|y| y.as_str())
+                        .map(|y| modifiers.apply(y))
+                        .collect::<Vec<_>>()
+                }) {
+                    if v.contains(&modifiers.apply(s)) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::StringDoesNotContain(ref s) => {
+                if let Some(v) = v.as_array().map(|x| {
+                    x.iter()
+                        .filter_map(|y| y.as_str())
+                        .map(|y| modifiers.apply(y))
+                        .collect::<Vec<_>>()
+                }) {
+                    if !v.contains(&modifiers.apply(s)) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::StringIn(ref ss) => {
+                if let Some(v) = v.as_str().map(|v| modifiers.apply(v)) {
+                    if ss.iter().any(|s| modifiers.apply(s) == v) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::StringNotIn(ref ss) => {
+                if let Some(v) = v.as_str().map(|v| modifiers.apply(v)) {
+                    if ss.iter().all(|s| modifiers.apply(s) != v) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::StringMatches(ref pattern) => {
+                if let Some(v) = v.as_str().map(|v| modifiers.apply_for_pattern(v)) {
+                    let matched = if modifiers.case_insensitive {
+                        pattern.is_match_case_insensitive(&v)
+                    } else {
+                        pattern.is_match(&v)
+                    };
+
+                    if matched {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::StringStartsWith(ref s) => {
+                if let Some(v) = v.as_str() {
+                    if modifiers.apply(v).starts_with(&*modifiers.apply(s)) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::StringEndsWith(ref s) => {
+                if let Some(v) = v.as_str() {
+                    if modifiers.apply(v).ends_with(&*modifiers.apply(s)) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::StringIncludesSubstring(ref s) => {
+                if let Some(v) = v.as_str() {
+                    if modifiers.apply(v).contains(&*modifiers.apply(s)) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::Glob(ref pattern) => {
+                if let Some(v) = v.as_str().map(|v| modifiers.apply_for_pattern(v)) {
+                    let matched = if modifiers.case_insensitive {
+                        pattern.is_match_case_insensitive(&v)
+                    } else {
+                        pattern.is_match(&v)
+                    };
+
+                    if matched {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::StringSimilar(ref similar) => {
+                if let Some(v) = v.as_str() {
+                    if similar
+                        .similarity
+                        .is_similar(&modifiers.apply(v), &modifiers.apply(&similar.target))
+                    {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::IntEquals(ref num) => {
+                if let Ok(val) = Integer::from_value(v) {
+                    if val == *num {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::IntNotEquals(ref num) => {
+                if let Ok(val) = Integer::from_value(v) {
+                    if val != *num {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::IntContains(ref num) => {
+                if let Some(val) = v.as_array().map(|x| {
+                    x.iter()
+                        .filter_map(|y| Integer::from_value(y).ok())
+                        .collect::<Vec<_>>()
+                }) {
+                    if val.contains(num) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::IntDoesNotContain(ref num) => {
+                if let Some(val) = v.as_array().map(|x| {
+                    x.iter()
+                        .filter_map(|y| Integer::from_value(y).ok())
+                        .collect::<Vec<_>>()
+                }) {
+                    if !val.contains(num) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::IntIn(ref nums) => {
+                if let Ok(val) = Integer::from_value(v) {
+                    if nums.contains(&val) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::IntNotIn(ref nums) => {
+                if let Ok(val) = Integer::from_value(v) {
+                    if nums.iter().all(|num| *num != val) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::IntInRange(ref start, ref end) => {
+                if let Ok(val) = Integer::from_value(v) {
+                    if *start <= val && val <= *end {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::IntNotInRange(ref start, ref end) => {
+                if let Ok(val) = Integer::from_value(v) {
+                    if *start <= val && val <= *end {
+                        Status::NotMet
+                    } else {
+                        Status::Met
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::IntLessThan(ref num) => {
+                if let Ok(val) = Integer::from_value(v) {
+                    if val < *num {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::IntLessThanInclusive(ref num) => {
+                if let Ok(val) = Integer::from_value(v) {
+                    if val <= *num {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::IntGreaterThan(ref num) => {
+                if let Ok(val) = Integer::from_value(v) {
+                    if val > *num {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::IntGreaterThanInclusive(ref num) => {
+                if let Ok(val) = Integer::from_value(v) {
+                    if val >= *num {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::FloatEquals(num) => {
+                if let Some(val) = v.as_f64() {
+                    if val == num {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::FloatNotEquals(num) => {
+                if let Some(val) = v.as_f64() {
+                    if val != num {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::FloatContains(num) => {
+                if let Some(val) = v
+                    .as_array()
+                    .map(|x| x.iter().filter_map(|y| y.as_f64()).collect::<Vec<f64>>())
+                {
+                    if val.contains(&num) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::FloatDoesNotContain(num) => {
+                if let Some(val) = v
+                    .as_array()
+                    .map(|x| x.iter().filter_map(|y| y.as_f64()).collect::<Vec<f64>>())
+                {
+                    if !val.contains(&num) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::FloatIn(ref nums) => {
+                if let Some(val) = v.as_f64() {
+                    if nums.contains(&val) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::FloatNotIn(ref nums) => {
+                if let Some(val) = v.as_f64() {
+                    if nums.iter().all(|&num| num != val) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::FloatInRange(start, end) => {
+                if let Some(val) = v.as_f64() {
+                    if start <= val && val <= end {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::FloatNotInRange(start, end) => {
+                if let Some(val) = v.as_f64() {
+                    if start <= val && val <= end {
+                        Status::NotMet
+                    } else {
+                        Status::Met
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::FloatLessThan(num) => {
+                if let Some(val) = v.as_f64() {
+                    if val < num {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::FloatLessThanInclusive(num) => {
+                if let Some(val) = v.as_f64() {
+                    if val <= num {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::FloatGreaterThan(num) => {
+                if let Some(val) = v.as_f64() {
+                    if val > num {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::FloatGreaterThanInclusive(num) => {
+                if let Some(val) = v.as_f64() {
+                    if val >= num {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::BoolEquals(b) => {
+                if let Some(val) = v.as_bool() {
+                    if val == b {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::DateBefore(Timestamp(t)) => {
+                if let Some(val) = parse_timestamp(v) {
+                    if val < t {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::DateAfter(Timestamp(t)) => {
+                if let Some(val) = parse_timestamp(v) {
+                    if val > t {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::DateBetween(Timestamp(start), Timestamp(end)) => {
+                if let Some(val) = parse_timestamp(v) {
+                    if start <= val && val <= end {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::WithinLast(Span(duration)) => {
+                if let Some(val) = parse_timestamp(v) {
+                    let now = options.now();
+                    // A span reaching before the earliest representable date
+                    // covers everything up to now
+                    let after_start = match now.checked_sub_signed(duration) {
+                        Some(start) => start <= val,
+                        None => true,
+                    };
+                    if after_start && val <= now {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::DayOfWeekIn(ref days) => {
+                if let Some(val) = parse_timestamp(v) {
+                    if days.contains(&val) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::TimeOfDayBetween(ref range) => {
+                if let Some(val) = parse_timestamp(v) {
+                    if range.contains(&val) {
+                        Status::Met
+                    } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::Exists => Status::Met,
+            Constraint::NotExists => Status::NotMet,
+            Constraint::IsNull => {
+                if v.is_null() {
+                    Status::Met
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::IsNotNull => {
+                if !v.is_null() {
                     Status::Met
                 } else {
                     Status::NotMet
-                };
-
-                ConditionResult {
-                    name: format!(
-                        "At least meet {} of {}",
-                        should_minimum_meet,
-                        conditions.len()
-                    ),
-                    status,
-                    children,
                 }
             }
-            Condition::Condition {
-                ref field,
-                ref constraint,
-            } => {
-                let pointer = if field.starts_with("/") {
-                    field.to_owned()
-                } else {
-                    format!("/{}", field)
-                };
-
-                let status = if let Some(s) = info.pointer(&pointer) {
-                    constraint.check_value(s)
+            Constraint::IsType(ty) => {
+                if ty.matches(v) {
+                    Status::Met
                 } else {
-                    Status::Unknown
-                };
-
-                ConditionResult {
-                    name: field.to_owned(),
-                    status,
-                    children: Vec::new(),
+                    Status::NotMet
                 }
             }
-        }
-    }
-}
-
-// ***********************************************************************
-// CONSTRAINT
-// **********************************************************************
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all(serialize = "snake_case"))]
-#[serde(tag = "operator", content = "value")]
-pub enum Constraint {
-    StringEquals(String),
-    StringNotEquals(String),
-    StringContains(String),
-    StringDoesNotContain(String),
-    StringIn(Vec<String>),
-    StringNotIn(Vec<String>),
-    IntEquals(i64),
-    IntNotEquals(i64),
-    IntContains(i64),
-    IntDoesNotContain(i64),
-    IntIn(Vec<i64>),
-    IntNotIn(Vec<i64>),
-    IntInRange(i64, i64),
-    IntNotInRange(i64, i64),
-    IntLessThan(i64),
-    IntLessThanInclusive(i64),
-    IntGreaterThan(i64),
-    IntGreaterThanInclusive(i64),
-    FloatEquals(f64),
-    FloatNotEquals(f64),
-    FloatContains(f64),
-    FloatDoesNotContain(f64),
-    FloatIn(Vec<f64>),
-    FloatNotIn(Vec<f64>),
-    FloatInRange(f64, f64),
-    FloatNotInRange(f64, f64),
-    FloatLessThan(f64),
-    FloatLessThanInclusive(f64),
-    FloatGreaterThan(f64),
-    FloatGreaterThanInclusive(f64),
-    BoolEquals(bool),
-}
-
-impl Constraint {
-    pub fn check_value(&self, v: &Value) -> Status {
-        match *self {
-            Constraint::StringEquals(ref s) => {
-                if let Some(v) = v.as_str() {
-                    if v == s {
+            Constraint::LengthEquals(len) => {
+                if let Some(val) = length(v, modifiers) {
+                    if val == len {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -328,9 +2094,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::StringNotEquals(ref s) => {
-                if let Some(v) = v.as_str() {
-                    if v != s {
+            Constraint::LengthGreaterThan(len) => {
+                if let Some(val) = length(v, modifiers) {
+                    if val > len {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -339,13 +2105,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::StringContains(ref s) => {
-                if let Some(v) = v.as_array().map(|x| {
-                    x.into_iter()
-                        .filter_map(|y| y.as_str())
-                        .collect::<Vec<&str>>()
-                }) {
-                    if v.contains(&s.as_str()) {
+            Constraint::LengthLessThan(len) => {
+                if let Some(val) = length(v, modifiers) {
+                    if val < len {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -354,13 +2116,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::StringDoesNotContain(ref s) => {
-                if let Some(v) = v.as_array().map(|x| {
-                    x.into_iter()
-                        .filter_map(|y| y.as_str())
-                        .collect::<Vec<&str>>()
-                }) {
-                    if !v.contains(&s.as_str()) {
+            Constraint::LengthInRange(start, end) => {
+                if let Some(val) = length(v, modifiers) {
+                    if start <= val && val <= end {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -369,9 +2127,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::StringIn(ref ss) => {
-                if let Some(v) = v.as_str() {
-                    if ss.iter().any(|s| s == v) {
+            Constraint::DecimalEquals(DecimalNumber(num)) => {
+                if let Some(val) = parse_decimal(v) {
+                    if val == num {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -380,9 +2138,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::StringNotIn(ref ss) => {
-                if let Some(v) = v.as_str() {
-                    if ss.iter().all(|s| s != v) {
+            Constraint::DecimalNotEquals(DecimalNumber(num)) => {
+                if let Some(val) = parse_decimal(v) {
+                    if val != num {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -391,9 +2149,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::IntEquals(num) => {
-                if let Some(val) = v.as_i64() {
-                    if val == num {
+            Constraint::DecimalIn(ref nums) => {
+                if let Some(val) = parse_decimal(v) {
+                    if nums.iter().any(|num| num.0 == val) {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -402,9 +2160,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::IntNotEquals(num) => {
-                if let Some(val) = v.as_i64() {
-                    if val != num {
+            Constraint::DecimalNotIn(ref nums) => {
+                if let Some(val) = parse_decimal(v) {
+                    if nums.iter().all(|num| num.0 != val) {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -413,13 +2171,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::IntContains(num) => {
-                if let Some(val) = v.as_array().map(|x| {
-                    x.into_iter()
-                        .filter_map(|y| y.as_i64())
-                        .collect::<Vec<i64>>()
-                }) {
-                    if val.contains(&num) {
+            Constraint::DecimalInRange(DecimalNumber(start), DecimalNumber(end)) => {
+                if let Some(val) = parse_decimal(v) {
+                    if start <= val && val <= end {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -428,13 +2182,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::IntDoesNotContain(num) => {
-                if let Some(val) = v.as_array().map(|x| {
-                    x.into_iter()
-                        .filter_map(|y| y.as_i64())
-                        .collect::<Vec<i64>>()
-                }) {
-                    if !val.contains(&num) {
+            Constraint::DecimalNotInRange(DecimalNumber(start), DecimalNumber(end)) => {
+                if let Some(val) = parse_decimal(v) {
+                    if !(start <= val && val <= end) {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -443,9 +2193,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::IntIn(ref nums) => {
-                if let Some(val) = v.as_i64() {
-                    if nums.iter().any(|&num| num == val) {
+            Constraint::DecimalLessThan(DecimalNumber(num)) => {
+                if let Some(val) = parse_decimal(v) {
+                    if val < num {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -454,9 +2204,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::IntNotIn(ref nums) => {
-                if let Some(val) = v.as_i64() {
-                    if nums.iter().all(|&num| num != val) {
+            Constraint::DecimalLessThanInclusive(DecimalNumber(num)) => {
+                if let Some(val) = parse_decimal(v) {
+                    if val <= num {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -465,9 +2215,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::IntInRange(start, end) => {
-                if let Some(val) = v.as_i64() {
-                    if start <= val && val <= end {
+            Constraint::DecimalGreaterThan(DecimalNumber(num)) => {
+                if let Some(val) = parse_decimal(v) {
+                    if val > num {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -476,20 +2226,20 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::IntNotInRange(start, end) => {
-                if let Some(val) = v.as_i64() {
-                    if start <= val && val <= end {
-                        Status::NotMet
-                    } else {
+            Constraint::DecimalGreaterThanInclusive(DecimalNumber(num)) => {
+                if let Some(val) = parse_decimal(v) {
+                    if val >= num {
                         Status::Met
+                    } else {
+                        Status::NotMet
                     }
                 } else {
                     Status::NotMet
                 }
             }
-            Constraint::IntLessThan(num) => {
-                if let Some(val) = v.as_i64() {
-                    if val < num {
+            Constraint::SemverEquals(ref version) => {
+                if let Some(val) = parse_version(v) {
+                    if val == *version {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -498,9 +2248,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::IntLessThanInclusive(num) => {
-                if let Some(val) = v.as_i64() {
-                    if val <= num {
+            Constraint::SemverGreaterThan(ref version) => {
+                if let Some(val) = parse_version(v) {
+                    if val > *version {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -509,9 +2259,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::IntGreaterThan(num) => {
-                if let Some(val) = v.as_i64() {
-                    if val > num {
+            Constraint::SemverLessThan(ref version) => {
+                if let Some(val) = parse_version(v) {
+                    if val < *version {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -520,9 +2270,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::IntGreaterThanInclusive(num) => {
-                if let Some(val) = v.as_i64() {
-                    if val >= num {
+            Constraint::SemverInRange(ref start, ref end) => {
+                if let Some(val) = parse_version(v) {
+                    if *start <= val && val <= *end {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -531,9 +2281,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::FloatEquals(num) => {
-                if let Some(val) = v.as_f64() {
-                    if val == num {
+            Constraint::SemverSatisfies(ref req) => {
+                if let Some(val) = parse_version(v) {
+                    if req.matches(&val) {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -542,9 +2292,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::FloatNotEquals(num) => {
-                if let Some(val) = v.as_f64() {
-                    if val != num {
+            Constraint::IpInCidr(ref cidrs) => {
+                if let Some(val) = parse_ip(v) {
+                    if cidrs.contains(val) {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -553,13 +2303,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::FloatContains(num) => {
-                if let Some(val) = v.as_array().map(|x| {
-                    x.into_iter()
-                        .filter_map(|y| y.as_f64())
-                        .collect::<Vec<f64>>()
-                }) {
-                    if val.contains(&num) {
+            Constraint::IpNotInCidr(ref cidrs) => {
+                if let Some(val) = parse_ip(v) {
+                    if !cidrs.contains(val) {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -568,13 +2314,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::FloatDoesNotContain(num) => {
-                if let Some(val) = v.as_array().map(|x| {
-                    x.into_iter()
-                        .filter_map(|y| y.as_f64())
-                        .collect::<Vec<f64>>()
-                }) {
-                    if !val.contains(&num) {
+            Constraint::IpEquals(ip) => {
+                if let Some(val) = parse_ip(v) {
+                    if val == canonical(ip) {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -583,9 +2325,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::FloatIn(ref nums) => {
-                if let Some(val) = v.as_f64() {
-                    if nums.iter().any(|&num| num == val) {
+            Constraint::IpIsPrivate => {
+                if let Some(val) = parse_ip(v) {
+                    if is_private(val) {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -594,9 +2336,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::FloatNotIn(ref nums) => {
-                if let Some(val) = v.as_f64() {
-                    if nums.iter().all(|&num| num != val) {
+            Constraint::GeoWithinRadius(ref radius) => {
+                if let Some(val) = parse_point(v) {
+                    if radius.contains(&val) {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -605,9 +2347,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::FloatInRange(start, end) => {
-                if let Some(val) = v.as_f64() {
-                    if start <= val && val <= end {
+            Constraint::GeoInPolygon(ref polygon) => {
+                if let Some(val) = parse_point(v) {
+                    if polygon.contains(&val) {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -616,20 +2358,42 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::FloatNotInRange(start, end) => {
-                if let Some(val) = v.as_f64() {
-                    if start <= val && val <= end {
+            Constraint::ContainsAll(ref vals) => {
+                if let Some(val) = v.as_array() {
+                    if vals.iter().all(|x| contains_value(val, x, modifiers)) {
+                        Status::Met
+                    } else {
                         Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::ContainsAny(ref vals) => {
+                if let Some(val) = v.as_array() {
+                    if vals.iter().any(|x| contains_value(val, x, modifiers)) {
+                        Status::Met
                     } else {
+                        Status::NotMet
+                    }
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::ContainsNone(ref vals) => {
+                if let Some(val) = v.as_array() {
+                    if !vals.iter().any(|x| contains_value(val, x, modifiers)) {
                         Status::Met
+                    } else {
+                        Status::NotMet
                     }
                 } else {
                     Status::NotMet
                 }
             }
-            Constraint::FloatLessThan(num) => {
-                if let Some(val) = v.as_f64() {
-                    if val < num {
+            Constraint::SubsetOf(ref vals) => {
+                if let Some(val) = v.as_array() {
+                    if val.iter().all(|x| contains_value(vals, x, modifiers)) {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -638,9 +2402,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::FloatLessThanInclusive(num) => {
-                if let Some(val) = v.as_f64() {
-                    if val <= num {
+            Constraint::SupersetOf(ref vals) => {
+                if let Some(val) = v.as_array() {
+                    if vals.iter().all(|x| contains_value(val, x, modifiers)) {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -649,9 +2413,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::FloatGreaterThan(num) => {
-                if let Some(val) = v.as_f64() {
-                    if val > num {
+            Constraint::Intersects(ref vals) => {
+                if let Some(val) = v.as_array() {
+                    if val.iter().any(|x| contains_value(vals, x, modifiers)) {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -660,9 +2424,14 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::FloatGreaterThanInclusive(num) => {
-                if let Some(val) = v.as_f64() {
-                    if val >= num {
+            Constraint::ArrayEquals(ref vals) => {
+                if let Some(val) = v.as_array() {
+                    if val.len() == vals.len()
+                        && val
+                            .iter()
+                            .zip(vals)
+                            .all(|(a, b)| values_eq(a, b, modifiers))
+                    {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -671,9 +2440,9 @@ impl Constraint {
                     Status::NotMet
                 }
             }
-            Constraint::BoolEquals(b) => {
-                if let Some(val) = v.as_bool() {
-                    if val == b {
+            Constraint::ArrayEqualsUnordered(ref vals) => {
+                if let Some(val) = v.as_array() {
+                    if same_elements(val, vals, modifiers) {
                         Status::Met
                     } else {
                         Status::NotMet
@@ -682,8 +2451,364 @@ impl Constraint {
                     Status::NotMet
                 }
             }
+            Constraint::Equals(ref val) => {
+                if values_eq(v, val, modifiers) {
+                    Status::Met
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::NotEquals(ref val) => {
+                if !values_eq(v, val, modifiers) {
+                    Status::Met
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::In(ref vals) => {
+                if contains_value(vals, v, modifiers) {
+                    Status::Met
+                } else {
+                    Status::NotMet
+                }
+            }
+            Constraint::MatchesSubset(ref val) => {
+                if matches_subset(v, val, modifiers) {
+                    Status::Met
+                } else {
+                    Status::NotMet
+                }
+            }
+        }
+    }
+}
+
+/// Structural equality of JSON values, numbers compare by value whatever their
+/// representation and strings honour the `Modifiers`
+fn values_eq(a: &Value, b: &Value, modifiers: &Modifiers) -> bool {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => modifiers.apply(a) == modifiers.apply(b),
+        (Value::Number(x), Value::Number(y)) => {
+            match (Integer::from_value(a), Integer::from_value(b)) {
+                (Ok(a), Ok(b)) => a == b,
+                _ => x.as_f64() == y.as_f64(),
+            }
+        }
+        (Value::Array(xs), Value::Array(ys)) => {
+            xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| values_eq(x, y, modifiers))
+        }
+        (Value::Object(xs), Value::Object(ys)) => {
+            xs.len() == ys.len()
+                && xs
+                    .iter()
+                    .all(|(k, x)| ys.get(k).is_some_and(|y| values_eq(x, y, modifiers)))
+        }
+        (a, b) => a == b,
+    }
+}
+
+/// Partial deep match: every key of an `expected` object must be in `v` and match
+/// recursively, and every element of an `expected` array must match some element
+/// of `v`. Anything else must be equal.
+fn matches_subset(v: &Value, expected: &Value, modifiers: &Modifiers) -> bool {
+    match (v, expected) {
+        (Value::Object(v), Value::Object(expected)) => expected
+            .iter()
+            .all(|(k, e)| v.get(k).is_some_and(|x| matches_subset(x, e, modifiers))),
+        (Value::Array(v), Value::Array(expected)) => expected
+            .iter()
+            .all(|e| v.iter().any(|x| matches_subset(x, e, modifiers))),
+        (v, expected) => values_eq(v, expected, modifiers),
+    }
+}
+
+fn contains_value(xs: &[Value], x: &Value, modifiers: &Modifiers) -> bool {
+    xs.iter().any(|y| values_eq(x, y, modifiers))
+}
+
+/// Whether both arrays have the same elements the same number of times, in any order
+fn same_elements(a: &[Value], b: &[Value], modifiers: &Modifiers) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut used = vec![false; b.len()];
+    a.iter().all(
+        |x| match (0..b.len()).find(|&i| !used[i] && values_eq(x, &b[i], modifiers)) {
+            Some(i) => {
+                used[i] = true;
+                true
+            }
+            None => false,
+        },
+    )
+}
+
+/// Length of a string in chars (after `trim`/`normalize`), or the number of
+/// elements of an array or keys of an object
+fn length(v: &Value, modifiers: &Modifiers) -> Option<usize> {
+    match *v {
+        Value::String(ref s) => Some(modifiers.apply_for_pattern(s).chars().count()),
+        Value::Array(ref a) => Some(a.len()),
+        Value::Object(ref o) => Some(o.len()),
+        _ => None,
+    }
+}
+
+/// Reads a fact as a semantic version, allowing a leading `v` as in `"v2.14.3"`
+fn parse_version(v: &Value) -> Option<Version> {
+    let s = v.as_str()?.trim();
+    Version::parse(s.strip_prefix('v').unwrap_or(s)).ok()
+}
+
+// ***********************************************************************
+// FACT CONSTRAINT
+// **********************************************************************
+/// Reference to another fact, used as the right-hand side of a comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FactReference {
+    pub fact: String,
+}
+
+impl FactReference {
+    /// Whether `v` is exactly `{"fact": "<field>"}`
+    fn is_reference(v: &Value) -> bool {
+        match v.as_object() {
+            Some(o) => o.len() == 1 && o.get("fact").is_some_and(Value::is_string),
+            None => false,
+        }
+    }
+}
+
+impl From<&str> for FactReference {
+    fn from(fact: &str) -> Self {
+        Self { fact: fact.into() }
+    }
+}
+
+/// Constraint that compares a fact against another fact rather than a literal
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[serde(tag = "operator", content = "value")]
+pub enum FactConstraint {
+    #[serde(alias = "StringEquals")]
+    StringEquals(FactReference),
+    #[serde(alias = "StringNotEquals")]
+    StringNotEquals(FactReference),
+    #[serde(alias = "StringContains")]
+    StringContains(FactReference),
+    #[serde(alias = "StringDoesNotContain")]
+    StringDoesNotContain(FactReference),
+    #[serde(alias = "StringIn")]
+    StringIn(FactReference),
+    #[serde(alias = "StringNotIn")]
+    StringNotIn(FactReference),
+    #[serde(alias = "IntEquals")]
+    IntEquals(FactReference),
+    #[serde(alias = "IntNotEquals")]
+    IntNotEquals(FactReference),
+    #[serde(alias = "IntContains")]
+    IntContains(FactReference),
+    #[serde(alias = "IntDoesNotContain")]
+    IntDoesNotContain(FactReference),
+    #[serde(alias = "IntIn")]
+    IntIn(FactReference),
+    #[serde(alias = "IntNotIn")]
+    IntNotIn(FactReference),
+    #[serde(alias = "IntLessThan")]
+    IntLessThan(FactReference),
+    #[serde(alias = "IntLessThanInclusive")]
+    IntLessThanInclusive(FactReference),
+    #[serde(alias = "IntGreaterThan")]
+    IntGreaterThan(FactReference),
+    #[serde(alias = "IntGreaterThanInclusive")]
+    IntGreaterThanInclusive(FactReference),
+    #[serde(alias = "FloatEquals")]
+    FloatEquals(FactReference),
+    #[serde(alias = "FloatNotEquals")]
+    FloatNotEquals(FactReference),
+    #[serde(alias = "FloatContains")]
+    FloatContains(FactReference),
+    #[serde(alias = "FloatDoesNotContain")]
+    FloatDoesNotContain(FactReference),
+    #[serde(alias = "FloatIn")]
+    FloatIn(FactReference),
+    #[serde(alias = "FloatNotIn")]
+    FloatNotIn(FactReference),
+    #[serde(alias = "FloatLessThan")]
+    FloatLessThan(FactReference),
+    #[serde(alias = "FloatLessThanInclusive")]
+    FloatLessThanInclusive(FactReference),
+    #[serde(alias = "FloatGreaterThan")]
+    FloatGreaterThan(FactReference),
+    #[serde(alias = "FloatGreaterThanInclusive")]
+    FloatGreaterThanInclusive(FactReference),
+    #[serde(alias = "BoolEquals")]
+    BoolEquals(FactReference),
+}
+
+impl FactConstraint {
+    /// Operators that have a fact-reference form, in their serialized names
+    pub const OPERATORS: &'static [&'static str] = &[
+        "string_equals",
+        "string_not_equals",
+        "string_contains",
+        "string_does_not_contain",
+        "string_in",
+        "string_not_in",
+        "int_equals",
+        "int_not_equals",
+        "int_contains",
+        "int_does_not_contain",
+        "int_in",
+        "int_not_in",
+        "int_less_than",
+        "int_less_than_inclusive",
+        "int_greater_than",
+        "int_greater_than_inclusive",
+        "float_equals",
+        "float_not_equals",
+        "float_contains",
+        "float_does_not_contain",
+        "float_in",
+        "float_not_in",
+        "float_less_than",
+        "float_less_than_inclusive",
+        "float_greater_than",
+        "float_greater_than_inclusive",
+        "bool_equals",
+    ];
+
+    /// Whether `operator` has a fact-reference form, by its snake_case name or the
+    /// PascalCase alias
+    pub fn supports(operator: &str) -> bool {
+        let mut name = String::with_capacity(operator.len() + 4);
+        for (i, c) in operator.char_indices() {
+            if c.is_ascii_uppercase() && i > 0 {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        }
+
+        Self::OPERATORS.contains(&name.as_str())
+    }
+
+    /// The fact this constraint compares against
+    pub fn fact(&self) -> &FactReference {
+        match *self {
+            FactConstraint::StringEquals(ref f)
+            | FactConstraint::StringNotEquals(ref f)
+            | FactConstraint::StringContains(ref f)
+            | FactConstraint::StringDoesNotContain(ref f)
+            | FactConstraint::StringIn(ref f)
+            | FactConstraint::StringNotIn(ref f)
+            | FactConstraint::IntEquals(ref f)
+            | FactConstraint::IntNotEquals(ref f)
+            | FactConstraint::IntContains(ref f)
+            | FactConstraint::IntDoesNotContain(ref f)
+            | FactConstraint::IntIn(ref f)
+            | FactConstraint::IntNotIn(ref f)
+            | FactConstraint::IntLessThan(ref f)
+            | FactConstraint::IntLessThanInclusive(ref f)
+            | FactConstraint::IntGreaterThan(ref f)
+            | FactConstraint::IntGreaterThanInclusive(ref f)
+            | FactConstraint::FloatEquals(ref f)
+            | FactConstraint::FloatNotEquals(ref f)
+            | FactConstraint::FloatContains(ref f)
+            | FactConstraint::FloatDoesNotContain(ref f)
+            | FactConstraint::FloatIn(ref f)
+            | FactConstraint::FloatNotIn(ref f)
+            | FactConstraint::FloatLessThan(ref f)
+            | FactConstraint::FloatLessThanInclusive(ref f)
+            | FactConstraint::FloatGreaterThan(ref f)
+            | FactConstraint::FloatGreaterThanInclusive(ref f)
+            | FactConstraint::BoolEquals(ref f) => f,
         }
     }
+
+    /// Builds the literal `Constraint` from the value of the referenced fact, converted
+    /// following the `Coercion` policy, or `None` if that value has the wrong type for
+    /// this operator
+    pub fn resolve(&self, other: &Value, coercion: Coercion) -> Option<Constraint> {
+        let as_string = |v: &Value| {
+            coerced(v, coercion, Coercion::for_string)
+                .as_str()
+                .map(ToOwned::to_owned)
+        };
+        let as_int = |v: &Value| Integer::from_value(&coerced(v, coercion, Coercion::for_int)).ok();
+        let as_float = |v: &Value| coerced(v, coercion, Coercion::for_float).as_f64();
+        let as_bool = |v: &Value| coerced(v, coercion, Coercion::for_bool).as_bool();
+
+        let strings = || {
+            other
+                .as_array()?
+                .iter()
+                .map(as_string)
+                .collect::<Option<Vec<String>>>()
+        };
+        let ints = || {
+            other
+                .as_array()?
+                .iter()
+                .map(as_int)
+                .collect::<Option<Vec<Integer>>>()
+        };
+        let floats = || {
+            other
+                .as_array()?
+                .iter()
+                .map(as_float)
+                .collect::<Option<Vec<f64>>>()
+        };
+        let string = || as_string(other);
+        let int = || as_int(other);
+        let float = || as_float(other);
+
+        Some(match *self {
+            FactConstraint::StringEquals(_) => Constraint::StringEquals(string()?),
+            FactConstraint::StringNotEquals(_) => Constraint::StringNotEquals(string()?),
+            FactConstraint::StringContains(_) => Constraint::StringContains(string()?),
+            FactConstraint::StringDoesNotContain(_) => Constraint::StringDoesNotContain(string()?),
+            FactConstraint::StringIn(_) => Constraint::StringIn(strings()?),
+            FactConstraint::StringNotIn(_) => Constraint::StringNotIn(strings()?),
+            FactConstraint::IntEquals(_) => Constraint::IntEquals(int()?),
+            FactConstraint::IntNotEquals(_) => Constraint::IntNotEquals(int()?),
+            FactConstraint::IntContains(_) => Constraint::IntContains(int()?),
+            FactConstraint::IntDoesNotContain(_) => Constraint::IntDoesNotContain(int()?),
+            FactConstraint::IntIn(_) => Constraint::IntIn(ints()?),
+            FactConstraint::IntNotIn(_) => Constraint::IntNotIn(ints()?),
+            FactConstraint::IntLessThan(_) => Constraint::IntLessThan(int()?),
+            FactConstraint::IntLessThanInclusive(_) => Constraint::IntLessThanInclusive(int()?),
+            FactConstraint::IntGreaterThan(_) => Constraint::IntGreaterThan(int()?),
+            FactConstraint::IntGreaterThanInclusive(_) => {
+                Constraint::IntGreaterThanInclusive(int()?)
+            }
+            FactConstraint::FloatEquals(_) => Constraint::FloatEquals(float()?),
+            FactConstraint::FloatNotEquals(_) => Constraint::FloatNotEquals(float()?),
+            FactConstraint::FloatContains(_) => Constraint::FloatContains(float()?),
+            FactConstraint::FloatDoesNotContain(_) => Constraint::FloatDoesNotContain(float()?),
+            FactConstraint::FloatIn(_) => Constraint::FloatIn(floats()?),
+            FactConstraint::FloatNotIn(_) => Constraint::FloatNotIn(floats()?),
+            FactConstraint::FloatLessThan(_) => Constraint::FloatLessThan(float()?),
+            FactConstraint::FloatLessThanInclusive(_) => {
+                Constraint::FloatLessThanInclusive(float()?)
+            }
+            FactConstraint::FloatGreaterThan(_) => Constraint::FloatGreaterThan(float()?),
+            FactConstraint::FloatGreaterThanInclusive(_) => {
+                Constraint::FloatGreaterThanInclusive(float()?)
+            }
+            FactConstraint::BoolEquals(_) => Constraint::BoolEquals(as_bool(other)?),
+        })
+    }
+}
+
+/// `v` converted by `convert` following the `Coercion` policy, or `v` itself
+fn coerced<'a>(
+    v: &'a Value,
+    coercion: Coercion,
+    convert: fn(Coercion, &Value) -> Option<Value>,
+) -> Cow<'a, Value> {
+    convert(coercion, v).map_or(Cow::Borrowed(v), Cow::Owned)
 }
 
 // ***********************************************************************
@@ -698,6 +2823,32 @@ pub struct ConditionResult {
     pub status: Status,
     /// Results of any sub-rules
     pub children: Vec<ConditionResult>,
+    /// Why a leaf is `NotMet` (or `Unknown`) when that's something other than a plain mismatch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<Reason>,
+}
+
+/// Explains a `NotMet` that doesn't come from the fact simply not matching
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    /// The fact is a number too large to be read exactly
+    OutOfRange,
+    /// The fact doesn't have the type the operator expects, e.g. a number for `string_equals`
+    TypeMismatch,
+    /// An expression divides by zero, so it has no value to check
+    DivisionByZero,
+}
+
+impl ConditionResult {
+    /// Finds the first result in this tree, depth-first, with the given `reason`
+    pub fn find_reason(&self, reason: Reason) -> Option<&ConditionResult> {
+        if self.reason == Some(reason) {
+            Some(self)
+        } else {
+            self.children.iter().find_map(|c| c.find_reason(reason))
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
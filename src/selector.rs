@@ -0,0 +1,367 @@
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// How the statuses of the values picked by a `Selector` are combined
+///
+/// A branch of a `*` or `..key` step that the rest of the selector doesn't resolve
+/// from counts as a missing field, so `/items/*/price` with an item that has no
+/// `price` is `Unknown` rather than `Met` for `All`, as `AllElements` would be.
+///
+/// A `*` step on an empty array or object selects no values, which like `AllElements`
+/// and `AnyElement` is `Met` for `All` and `NotMet` for `Any`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Quantifier {
+    /// `Met` if the constraint is `Met` for any value
+    Any,
+    /// `Met` if the constraint is `Met` for every value
+    All,
+}
+
+/// Field selector that can pick several values from the facts
+///
+/// Either a JSON pointer where `*` matches every element of an array or every value
+/// of an object, as in `/items/*/price`, or a JSONPath starting with `$` such as
+/// `$.items[*].price`. The JSONPath subset supported is `.key`, `['key']`, `[0]`,
+/// `.*`, `[*]` and `..key` (any descendant named `key`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    source: String,
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Key(String),
+    Index(usize),
+    Wildcard,
+    Descendant(String),
+}
+
+impl Selector {
+    /// Every value matched in `info`, along with its JSON pointer
+    ///
+    /// A branch of a `*` or `..key` step that the rest of the selector doesn't resolve
+    /// from, such as an item without a `price` for `/items/*/price`, is returned with
+    /// the pointer of that branch and no value.
+    pub fn select<'a>(&self, info: &'a Value) -> Vec<(String, Option<&'a Value>)> {
+        self.select_resolved(info).0
+    }
+
+    /// Same as `select`, also telling whether the selector resolved in `info`, which it
+    /// does with no values when a `*` step reaches an empty array or object
+    pub(crate) fn select_resolved<'a>(
+        &self,
+        info: &'a Value,
+    ) -> (Vec<(String, Option<&'a Value>)>, bool) {
+        let mut matches = Vec::new();
+        let mut walker = Walker {
+            path: Some(String::new()),
+            f: &mut |path: &str, v| {
+                matches.push((path.to_owned(), v));
+                true
+            },
+        };
+        let resolved = walker.walk(&self.steps, info).unwrap_or(true);
+
+        (matches, resolved)
+    }
+
+    /// Calls `f` on every value matched in `info`, `None` for an unresolved branch as
+    /// in `select`, until it returns `false`, without building the pointers
+    ///
+    /// Returns whether the selector resolved, as `select_resolved` does.
+    pub(crate) fn visit<'a>(
+        &self,
+        info: &'a Value,
+        f: &mut dyn FnMut(Option<&'a Value>) -> bool,
+    ) -> bool {
+        let mut walker = Walker {
+            path: None,
+            f: &mut |_: &str, v| f(v),
+        };
+        walker.walk(&self.steps, info).unwrap_or(true)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Returned when the callback stops the walk
+struct Stopped;
+
+/// Depth-first walk of the steps, in document order
+struct Walker<'a, 'f> {
+    /// Pointer of the current value, if the caller wants it
+    path: Option<String>,
+    f: &'f mut dyn FnMut(&str, Option<&'a Value>) -> bool,
+}
+
+enum Segment<'s> {
+    Key(&'s str),
+    Index(usize),
+}
+
+impl<'a> Walker<'a, '_> {
+    /// Walks `steps` from `v`, returns whether they resolved: anything was reported, or
+    /// a `*` step reached an empty array or object
+    fn walk(&mut self, steps: &[Step], v: &'a Value) -> Result<bool, Stopped> {
+        let (step, rest) = match steps.split_first() {
+            Some(split) => split,
+            None => return self.report(Some(v)),
+        };
+
+        match (step, v) {
+            (Step::Key(key), Value::Object(o)) => match o.get(key) {
+                Some(x) => self.child(rest, Segment::Key(key), x, false),
+                None => Ok(false),
+            },
+            (Step::Key(key), Value::Array(a)) => {
+                match key.parse::<usize>().ok().and_then(|i| a.get(i)) {
+                    Some(x) => self.child(rest, Segment::Key(key), x, false),
+                    None => Ok(false),
+                }
+            }
+            (Step::Index(i), Value::Array(a)) => match a.get(*i) {
+                Some(x) => self.child(rest, Segment::Index(*i), x, false),
+                None => Ok(false),
+            },
+            (Step::Wildcard, Value::Object(o)) => {
+                let mut found = false;
+                for (k, x) in o {
+                    found |= self.child(rest, Segment::Key(k), x, true)?;
+                }
+                Ok(found || o.is_empty())
+            }
+            (Step::Wildcard, Value::Array(a)) => {
+                let mut found = false;
+                for (i, x) in a.iter().enumerate() {
+                    found |= self.child(rest, Segment::Index(i), x, true)?;
+                }
+                Ok(found || a.is_empty())
+            }
+            (Step::Descendant(key), _) => self.descendants(key, rest, v),
+            _ => Ok(false),
+        }
+    }
+
+    /// Walks the `rest` of the steps from every value named `key` at or below `v`
+    fn descendants(&mut self, key: &str, rest: &[Step], v: &'a Value) -> Result<bool, Stopped> {
+        let mut found = false;
+
+        match v {
+            Value::Object(o) => {
+                if let Some(x) = o.get(key) {
+                    found |= self.child(rest, Segment::Key(key), x, true)?;
+                }
+                for (k, x) in o {
+                    let len = self.push(Segment::Key(k));
+                    found |= self.descendants(key, rest, x)?;
+                    self.truncate(len);
+                }
+            }
+            Value::Array(a) => {
+                for (i, x) in a.iter().enumerate() {
+                    let len = self.push(Segment::Index(i));
+                    found |= self.descendants(key, rest, x)?;
+                    self.truncate(len);
+                }
+            }
+            _ => {}
+        }
+
+        Ok(found)
+    }
+
+    /// Walks the `rest` of the steps from `x`, reporting it as unresolved if it's
+    /// a `branch` they don't resolve from
+    fn child(
+        &mut self,
+        rest: &[Step],
+        segment: Segment<'_>,
+        x: &'a Value,
+        branch: bool,
+    ) -> Result<bool, Stopped> {
+        let len = self.push(segment);
+        let mut found = self.walk(rest, x)?;
+        if branch && !found {
+            found = self.report(None)?;
+        }
+        self.truncate(len);
+
+        Ok(found)
+    }
+
+    fn report(&mut self, v: Option<&'a Value>) -> Result<bool, Stopped> {
+        if (self.f)(self.path.as_deref().unwrap_or_default(), v) {
+            Ok(true)
+        } else {
+            Err(Stopped)
+        }
+    }
+
+    /// Appends `segment` to the pointer, escaping it as RFC 6901 requires, and returns
+    /// the previous length
+    fn push(&mut self, segment: Segment<'_>) -> usize {
+        let path = match self.path {
+            Some(ref mut path) => path,
+            None => return 0,
+        };
+        let len = path.len();
+
+        path.push('/');
+        match segment {
+            Segment::Key(key) => {
+                for c in key.chars() {
+                    match c {
+                        '~' => path.push_str("~0"),
+                        '/' => path.push_str("~1"),
+                        c => path.push(c),
+                    }
+                }
+            }
+            Segment::Index(i) => {
+                let _ = write!(path, "{}", i);
+            }
+        }
+
+        len
+    }
+
+    fn truncate(&mut self, len: usize) {
+        if let Some(ref mut path) = self.path {
+            path.truncate(len);
+        }
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Serialize for Selector {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for Selector {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+// ***********************************************************************
+// PARSER
+// **********************************************************************
+/// Error returned when a selector can't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorParseError {
+    pub message: String,
+    /// Byte offset in the selector where the error was found
+    pub position: usize,
+}
+
+impl fmt::Display for SelectorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for SelectorParseError {}
+
+impl FromStr for Selector {
+    type Err = SelectorParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let steps = if s.starts_with('$') {
+            parse_json_path(s)?
+        } else {
+            s.strip_prefix('/')
+                .unwrap_or(s)
+                .split('/')
+                .map(|key| match key {
+                    "*" => Step::Wildcard,
+                    key => Step::Key(key.replace("~1", "/").replace("~0", "~")),
+                })
+                .collect()
+        };
+
+        Ok(Selector {
+            source: s.into(),
+            steps,
+        })
+    }
+}
+
+fn parse_json_path(s: &str) -> Result<Vec<Step>, SelectorParseError> {
+    let error = |message: &str, position: usize| SelectorParseError {
+        message: message.into(),
+        position,
+    };
+    let is_name = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    let name_end = |from: usize| {
+        s[from..]
+            .find(|c: char| !is_name(c))
+            .map_or(s.len(), |i| from + i)
+    };
+
+    let mut steps = Vec::new();
+    let mut pos = 1;
+
+    while pos < s.len() {
+        let rest = &s[pos..];
+
+        if rest.starts_with("..") {
+            let start = pos + 2;
+            let end = name_end(start);
+            if end == start {
+                return Err(error("expected a name after `..`", start));
+            }
+            steps.push(Step::Descendant(s[start..end].into()));
+            pos = end;
+        } else if rest.starts_with(".*") {
+            steps.push(Step::Wildcard);
+            pos += 2;
+        } else if rest.starts_with('.') {
+            let start = pos + 1;
+            let end = name_end(start);
+            if end == start {
+                return Err(error("expected a name after `.`", start));
+            }
+            steps.push(Step::Key(s[start..end].into()));
+            pos = end;
+        } else if rest.starts_with('[') {
+            let close = rest
+                .find(']')
+                .ok_or_else(|| error("expected `]`", s.len()))?;
+            let inner = &rest[1..close];
+
+            steps.push(if inner == "*" {
+                Step::Wildcard
+            } else if let Ok(i) = inner.parse::<usize>() {
+                Step::Index(i)
+            } else if inner.len() >= 2
+                && (inner.starts_with('\'') && inner.ends_with('\'')
+                    || inner.starts_with('"') && inner.ends_with('"'))
+            {
+                Step::Key(inner[1..inner.len() - 1].into())
+            } else {
+                return Err(error("expected `*`, an index or a quoted key", pos + 1));
+            });
+            pos += close + 1;
+        } else {
+            return Err(error("expected `.` or `[`", pos));
+        }
+    }
+
+    Ok(steps)
+}
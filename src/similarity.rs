@@ -0,0 +1,91 @@
+use std::fmt;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+/// Algorithm and threshold used by `string_similar`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "algorithm", rename_all = "snake_case")]
+pub enum Similarity {
+    /// At most `max_distance` single-character insertions, deletions or substitutions
+    Levenshtein { max_distance: usize },
+    /// Jaro-Winkler similarity of at least `min_similarity`, between 0 and 1.
+    /// It favours strings sharing a prefix, which suits names
+    JaroWinkler { min_similarity: f64 },
+    /// Levenshtein distance relative to the longer string's length, as a similarity
+    /// of at least `min_similarity`, between 0 and 1
+    NormalizedLevenshtein { min_similarity: f64 },
+}
+
+/// Target string of `string_similar` along with how close a fact has to be
+///
+/// Given in a rule as `{"target": "Jon Smith", "algorithm": "levenshtein", "max_distance": 2}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimilarTo {
+    pub target: String,
+    #[serde(flatten)]
+    pub similarity: Similarity,
+}
+
+/// A `min_similarity` outside `[0, 1]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidSimilarity(pub f64);
+
+impl fmt::Display for InvalidSimilarity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "min_similarity `{}` isn't between 0 and 1", self.0)
+    }
+}
+
+impl std::error::Error for InvalidSimilarity {}
+
+impl Similarity {
+    /// Checks that `min_similarity` is between 0 and 1, NaN being rejected too
+    pub fn validate(&self) -> Result<(), InvalidSimilarity> {
+        match *self {
+            Similarity::Levenshtein { .. } => Ok(()),
+            Similarity::JaroWinkler { min_similarity }
+            | Similarity::NormalizedLevenshtein { min_similarity } => {
+                if (0.0..=1.0).contains(&min_similarity) {
+                    Ok(())
+                } else {
+                    Err(InvalidSimilarity(min_similarity))
+                }
+            }
+        }
+    }
+
+    pub fn is_similar(&self, a: &str, b: &str) -> bool {
+        match *self {
+            Similarity::Levenshtein { max_distance } => strsim::levenshtein(a, b) <= max_distance,
+            Similarity::JaroWinkler { min_similarity } => {
+                strsim::jaro_winkler(a, b) >= min_similarity
+            }
+            Similarity::NormalizedLevenshtein { min_similarity } => {
+                strsim::normalized_levenshtein(a, b) >= min_similarity
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Similarity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(tag = "algorithm", rename_all = "snake_case")]
+        enum Raw {
+            Levenshtein { max_distance: usize },
+            JaroWinkler { min_similarity: f64 },
+            NormalizedLevenshtein { min_similarity: f64 },
+        }
+
+        let similarity = match Raw::deserialize(deserializer)? {
+            Raw::Levenshtein { max_distance } => Similarity::Levenshtein { max_distance },
+            Raw::JaroWinkler { min_similarity } => Similarity::JaroWinkler { min_similarity },
+            Raw::NormalizedLevenshtein { min_similarity } => {
+                Similarity::NormalizedLevenshtein { min_similarity }
+            }
+        };
+
+        similarity.validate().map_err(de::Error::custom)?;
+        Ok(similarity)
+    }
+}